@@ -0,0 +1,92 @@
+//! Color calibration procedure.
+//!
+//! For each light source, place the sensor and a reference colorimeter at the same position,
+//! then enter the X, Y and Z values measured by the reference device separated by spaces.
+//! The color / ambient light data is measured at the same time.
+//! After at least four light sources, enter an empty line to compute the calibration matrix
+//! with a least squares fit.
+use apds9960::{color::CalibrationMatrix, Apds9960, LightData, LightGain};
+use linux_embedded_hal::I2cdev;
+use nb::block;
+use std::io::BufRead;
+
+fn main() {
+    let dev = I2cdev::new("/dev/i2c-1").unwrap();
+    let mut sensor = Apds9960::new(dev);
+    sensor.set_light_integration_cycles(36).unwrap();
+    sensor.set_light_gain(LightGain::X4).unwrap();
+    sensor.enable().unwrap();
+    sensor.enable_light().unwrap();
+    let max_count = sensor.max_light_count();
+
+    let mut measurements: Vec<(LightData, [f64; 3])> = Vec::new();
+    println!("Enter reference X Y Z values:");
+    for line in std::io::stdin().lock().lines() {
+        let line = line.unwrap();
+        if line.trim().is_empty() {
+            break;
+        }
+        let reference: Vec<f64> = line
+            .split_whitespace()
+            .map(|value| value.parse().unwrap())
+            .collect();
+        let data = block!(sensor.read_light()).unwrap();
+        if data.clear >= max_count {
+            println!("Saturated. Please reduce the light intensity.");
+            continue;
+        }
+        println!("Measured: {:?}", data);
+        measurements.push((data, [reference[0], reference[1], reference[2]]));
+    }
+    assert!(
+        measurements.len() >= 4,
+        "At least 4 light sources are needed"
+    );
+
+    let mut coefficients = [[0; 4]; 3];
+    for (row, coefficients) in coefficients.iter_mut().enumerate() {
+        // Normal equations: (AᵀA) m = Aᵀb
+        let mut ata = [[0.0; 4]; 4];
+        let mut atb = [0.0; 4];
+        for (data, reference) in &measurements {
+            let channels = [data.red, data.green, data.blue, data.clear].map(f64::from);
+            for i in 0..4 {
+                for j in 0..4 {
+                    ata[i][j] += channels[i] * channels[j];
+                }
+                atb[i] += channels[i] * reference[row];
+            }
+        }
+        let solution = solve(ata, atb);
+        for (c, s) in coefficients.iter_mut().zip(solution.iter()) {
+            *c = (s * 100_000.0).round() as i32;
+        }
+    }
+    let matrix = CalibrationMatrix::new(coefficients);
+    println!("{:?}", matrix);
+}
+
+/// Solve a 4x4 linear equation system with Gaussian elimination.
+fn solve(mut a: [[f64; 4]; 4], mut b: [f64; 4]) -> [f64; 4] {
+    for col in 0..4 {
+        let pivot = (col..4)
+            .max_by(|&i, &j| a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap())
+            .unwrap();
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col];
+        for row in col + 1..4 {
+            let factor = a[row][col] / pivot_row[col];
+            for (value, pivot) in a[row].iter_mut().zip(pivot_row.iter()).skip(col) {
+                *value -= factor * pivot;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = [0.0; 4];
+    for row in (0..4).rev() {
+        let sum: f64 = (row + 1..4).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    x
+}
@@ -1,11 +1,6 @@
-extern crate embedded_hal;
-extern crate linux_embedded_hal;
-#[macro_use]
-extern crate nb;
-extern crate apds9960;
-
 use apds9960::Apds9960;
 use linux_embedded_hal::I2cdev;
+use nb::block;
 
 fn main() {
     let dev = I2cdev::new("/dev/i2c-1").unwrap();
@@ -0,0 +1,81 @@
+//! Common parts of the proximity and gesture crosstalk calibrations.
+
+/// Number of samples averaged for each offset value.
+const SAMPLE_COUNT: u16 = 4;
+/// Maximum average value considered as no crosstalk.
+const BASELINE_TARGET: u16 = 1;
+/// Maximum number of times the data is polled before giving up.
+pub(crate) const MAX_POLLS: u16 = 1000;
+
+/// Binary search of the smallest non-negative offset for which there is no crosstalk.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OffsetSearch {
+    low: u8,
+    high: u8,
+}
+
+impl OffsetSearch {
+    pub(crate) fn new() -> Self {
+        OffsetSearch {
+            low: 0,
+            high: i8::MAX as u8,
+        }
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.low >= self.high
+    }
+
+    /// Offset to be tried next or the result once done.
+    pub(crate) fn offset(&self) -> i8 {
+        ((self.low + self.high) / 2) as i8
+    }
+
+    /// Narrow the search down with the baseline measured with the current offset.
+    pub(crate) fn update(&mut self, baseline: u16) {
+        if self.is_done() {
+            return;
+        }
+        let offset = self.offset() as u8;
+        if baseline <= BASELINE_TARGET {
+            self.high = offset;
+        } else {
+            self.low = offset + 1;
+        }
+    }
+}
+
+/// Average of several samples of `N` channels.
+///
+/// The first sample is discarded as it may have been measured with the previous settings.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Baseline<const N: usize> {
+    sum: [u16; N],
+    count: u16,
+}
+
+impl<const N: usize> Baseline<N> {
+    pub(crate) fn new() -> Self {
+        Baseline {
+            sum: [0; N],
+            count: 0,
+        }
+    }
+
+    pub(crate) fn is_complete(&self) -> bool {
+        self.count > SAMPLE_COUNT
+    }
+
+    pub(crate) fn add(&mut self, sample: [u8; N]) {
+        if self.count > 0 {
+            for (sum, value) in self.sum.iter_mut().zip(sample.iter()) {
+                *sum += u16::from(*value);
+            }
+        }
+        self.count += 1;
+    }
+
+    pub(crate) fn average(&self) -> [u16; N] {
+        self.sum.map(|sum| sum / SAMPLE_COUNT)
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{
+    color::CalibrationMatrix, IrCompensatedLightData, LightCoefficients, LightData, LightSettings,
+};
+
+impl LightData {
+    /// Compute the correlated color temperature (CCT) in Kelvin from the ratio of
+    /// the blue and red channels.
+    ///
+    /// The infrared component is estimated and removed from the red and blue channels.
+    /// See [`IrCompensatedLightData::cct()`](struct.IrCompensatedLightData.html#method.cct).
+    ///
+    /// Returns `None` if the clear channel is saturated for the given settings or if
+    /// the result is not meaningful, e.g. because there is no red light.
+    pub fn cct(&self, settings: &LightSettings, coefficients: &LightCoefficients) -> Option<u32> {
+        self.ir_compensated().cct(settings, coefficients)
+    }
+
+    /// Compute the correlated color temperature (CCT) in Kelvin using McCamy's approximation.
+    ///
+    /// The data is converted to CIE 1931 XYZ with the default
+    /// [`CalibrationMatrix`](color/struct.CalibrationMatrix.html) and the resulting chromaticity
+    /// coordinates are used in McCamy's formula. See [`Chromaticity::cct()`](color/struct.Chromaticity.html#method.cct).
+    /// To use a custom calibration matrix, use the [`color`](color/index.html) module directly.
+    ///
+    /// Returns `None` if the clear channel is saturated for the given settings or if
+    /// the chromaticity is out of the range where the approximation is valid.
+    ///
+    /// Only integer arithmetic is used.
+    pub fn cct_mccamy(&self, settings: &LightSettings) -> Option<u32> {
+        if self.clear >= settings.max_count() {
+            return None;
+        }
+        CalibrationMatrix::default().xyz(self).chromaticity()?.cct()
+    }
+}
+
+impl IrCompensatedLightData {
+    /// Compute the correlated color temperature (CCT) in Kelvin from the ratio of
+    /// the blue and red channels.
+    ///
+    /// `CCT = ct_coef * blue / red + ct_offset` as in the ams DN40 application note.
+    /// The color temperature coefficient and offset can be calibrated for a cover glass
+    /// through the given coefficients.
+    ///
+    /// Returns `None` if the clear channel is saturated for the given settings or if
+    /// the result is not meaningful, e.g. because there is no red light.
+    pub fn cct(&self, settings: &LightSettings, coefficients: &LightCoefficients) -> Option<u32> {
+        if self.is_saturated(settings.max_count()) || self.red <= 0 {
+            return None;
+        }
+        let cct = i64::from(coefficients.ct_coef) * i64::from(self.blue) / i64::from(self.red)
+            + i64::from(coefficients.ct_offset);
+        positive_u32(cct)
+    }
+}
+
+fn positive_u32(value: i64) -> Option<u32> {
+    if value > 0 && value <= i64::from(u32::MAX) {
+        Some(value as u32)
+    } else {
+        None
+    }
+}
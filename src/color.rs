@@ -0,0 +1,184 @@
+//! Color conversion of the color / ambient light data into CIE 1931 XYZ
+//! tristimulus values and xy chromaticity coordinates.
+//!
+//! The conversion is done with a 3×4 [`CalibrationMatrix`] which is applied to the
+//! red, green, blue and clear channel counts. Only integer arithmetic is used.
+//!
+//! ```
+//! use apds9960::{color::CalibrationMatrix, LightData};
+//!
+//! let data = LightData { clear: 1000, red: 400, green: 450, blue: 300 };
+//! let xyz = CalibrationMatrix::default().xyz(&data);
+//! let chromaticity = xyz.chromaticity().unwrap();
+//! let cct = chromaticity.cct().unwrap();
+//! ```
+//!
+//! ## Calibration
+//!
+//! The default matrix is a generic one. The spectral response of each product depends
+//! on the cover window, so for accurate results a matrix should be determined
+//! for each product as follows:
+//!
+//! 1. Take color / ambient light measurements of at least four (ideally many more)
+//!    light sources with different spectra, e.g. incandescent, fluorescent, LEDs and daylight.
+//!    Use the same integration time and gain for all of them and make sure the data
+//!    is not saturated.
+//! 2. For each light source, take a reading of the XYZ tristimulus values with a reference
+//!    colorimeter or spectrometer at the same position.
+//! 3. Find the coefficients `[r, g, b, c]` of each row of the matrix with a least squares
+//!    fit of `r * red + g * green + b * blue + c * clear` to the X, Y and Z reference
+//!    values respectively.
+//! 4. Multiply the coefficients by 100000, round them to integers and create the matrix
+//!    with [`CalibrationMatrix::new()`].
+//!
+//! The `color_calibration` example in this repository implements this procedure.
+//! If the reference values are given in lux (i.e. Y is the illuminance), the Y value
+//! obtained with the resulting matrix will be the illuminance for the integration time and
+//! gain used for the calibration.
+
+use crate::{IrCompensatedLightData, LightData};
+
+/// Matrix for the conversion of color / ambient light data into CIE 1931 XYZ.
+///
+/// Each row contains the coefficients for the red, green, blue and clear channels,
+/// in 1/100000 units, used to compute X, Y and Z respectively.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationMatrix {
+    /// Coefficients for X, Y and Z in 1/100000 units.
+    pub coefficients: [[i32; 4]; 3],
+}
+
+impl CalibrationMatrix {
+    /// Create a calibration matrix from coefficients in 1/100000 units.
+    ///
+    /// Each row contains the coefficients for the red, green, blue and clear channels
+    /// used to compute X, Y and Z respectively.
+    pub fn new(coefficients: [[i32; 4]; 3]) -> Self {
+        CalibrationMatrix { coefficients }
+    }
+
+    /// Convert color / ambient light data into CIE 1931 XYZ tristimulus values.
+    pub fn xyz(&self, data: &LightData) -> Xyz {
+        self.convert([
+            i64::from(data.red),
+            i64::from(data.green),
+            i64::from(data.blue),
+            i64::from(data.clear),
+        ])
+    }
+
+    /// Convert color / ambient light data with the infrared component removed into
+    /// CIE 1931 XYZ tristimulus values.
+    ///
+    /// The calibration matrix must have been determined with infrared-compensated data as well.
+    pub fn xyz_ir_compensated(&self, data: &IrCompensatedLightData) -> Xyz {
+        self.convert([
+            i64::from(data.red),
+            i64::from(data.green),
+            i64::from(data.blue),
+            i64::from(data.clear),
+        ])
+    }
+
+    fn convert(&self, channels: [i64; 4]) -> Xyz {
+        let mut xyz = [0; 3];
+        for (value, row) in xyz.iter_mut().zip(self.coefficients.iter()) {
+            let sum: i64 = row
+                .iter()
+                .zip(channels.iter())
+                .map(|(m, c)| i64::from(*m) * c)
+                .sum();
+            *value = (sum / 1000).clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32;
+        }
+        Xyz {
+            x: xyz[0],
+            y: xyz[1],
+            z: xyz[2],
+        }
+    }
+}
+
+impl Default for CalibrationMatrix {
+    /// Generic matrix for the device without cover glass. The clear channel is not used.
+    fn default() -> Self {
+        CalibrationMatrix::new([
+            [-14282, 154924, -95641, 0],
+            [-32466, 157837, -73191, 0],
+            [-68202, 77073, 56332, 0],
+        ])
+    }
+}
+
+/// CIE 1931 XYZ tristimulus values.
+///
+/// The values are given in hundredths of the unit of the calibration (e.g. counts or lux).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Xyz {
+    /// X value.
+    pub x: i32,
+    /// Y value.
+    pub y: i32,
+    /// Z value.
+    pub z: i32,
+}
+
+impl Xyz {
+    /// Compute the CIE 1931 xy chromaticity coordinates.
+    ///
+    /// Returns `None` if any of the values is negative or all of them are zero.
+    pub fn chromaticity(&self) -> Option<Chromaticity> {
+        if self.x < 0 || self.y < 0 || self.z < 0 {
+            return None;
+        }
+        let sum = i64::from(self.x) + i64::from(self.y) + i64::from(self.z);
+        if sum == 0 {
+            return None;
+        }
+        Some(Chromaticity {
+            x: (i64::from(self.x) * 1_000_000 / sum) as u32,
+            y: (i64::from(self.y) * 1_000_000 / sum) as u32,
+        })
+    }
+}
+
+/// CIE 1931 xy chromaticity coordinates in millionths.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Chromaticity {
+    /// x coordinate in millionths.
+    pub x: u32,
+    /// y coordinate in millionths.
+    pub y: u32,
+}
+
+impl Chromaticity {
+    /// Compute the correlated color temperature (CCT) in Kelvin using McCamy's approximation.
+    ///
+    /// `n = (x - 0.3320) / (0.1858 - y)`, `CCT = 449n³ + 3525n² + 6823.3n + 5520.33`
+    ///
+    /// Returns `None` if the chromaticity is out of the range where the approximation is valid.
+    pub fn cct(&self) -> Option<u32> {
+        let x = i64::from(self.x);
+        let y = i64::from(self.y);
+        let denominator = 185_800 - y;
+        if denominator == 0 {
+            return None;
+        }
+        // in 1/10000 units
+        let n = (x - 332_000) * 10_000 / denominator;
+        // Outside of this range the approximation is not useful and the
+        // computation below could overflow.
+        if n.abs() > 50_000 {
+            return None;
+        }
+        // in 1/100 K
+        let cct = 44_900 * n * n * n / 1_000_000_000_000
+            + 352_500 * n * n / 100_000_000
+            + 682_330 * n / 10_000
+            + 552_033;
+        if cct >= 100 {
+            Some((cct / 100) as u32)
+        } else {
+            None
+        }
+    }
+}
@@ -1,33 +1,79 @@
-use hal::blocking::i2c;
-use {
-    register::{Config1, Enable},
+#[cfg(feature = "async")]
+use crate::Apds9960Async;
+use crate::{
+    register::{
+        ATime, Config1, Config2, Config3, Control, Enable, GConfig1, GConfig2, GConfig3, GConfig4,
+        GPulse, PPulse, Pers,
+    },
     Apds9960, BitFlags, Error, Register, DEV_ADDR,
 };
+use core::time::Duration;
 
-macro_rules! impl_set_flag_reg {
-    ($method:ident, $reg:ident) => {
-        pub(crate) fn $method(&mut self, flag: u8, value: bool) -> Result<(), Error<E>> {
-            let new = self.$reg.with(flag, value);
-            self.config_register(&new)?;
-            self.$reg = new;
-            Ok(())
-        }
-    };
+/// Duration of an integration or wait cycle in microseconds.
+pub(crate) const CYCLE_US: u32 = 2780;
+
+/// Number of cycles of the given duration nearest to a duration (at least one).
+///
+/// Returns `None` if this is more than 256 cycles.
+pub(crate) fn duration_to_cycles(duration: Duration, cycle_us: u32) -> Option<u16> {
+    let cycle_us = u128::from(cycle_us);
+    let cycles = core::cmp::max(1, (duration.as_micros() + cycle_us / 2) / cycle_us);
+    if cycles > 256 {
+        None
+    } else {
+        Some(cycles as u16)
+    }
+}
+
+/// Duration of the given number of cycles.
+pub(crate) fn cycles_to_duration(cycles: u16, cycle_us: u32) -> Duration {
+    Duration::from_micros(u64::from(cycles) * u64::from(cycle_us))
+}
+
+/// Sign-magnitude register value of a photodiode offset.
+///
+/// Returns `None` for -128, which cannot be represented.
+pub(crate) fn offset_to_register(offset: i8) -> Option<u8> {
+    if offset == i8::MIN {
+        None
+    } else if offset < 0 {
+        Some(0x80 | offset.unsigned_abs())
+    } else {
+        Some(offset as u8)
+    }
+}
+
+/// Photodiode offset of a sign-magnitude register value.
+pub(crate) fn register_to_offset(value: u8) -> i8 {
+    let magnitude = (value & 0x7F) as i8;
+    if value & 0x80 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
 }
 
 /// Common configuration.
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async"),
+    idents(
+        Apds9960(sync, async = "Apds9960Async"),
+        embedded_hal(sync, async = "embedded_hal_async")
+    )
+)]
 impl<I2C, E> Apds9960<I2C>
 where
-    I2C: i2c::Write<Error = E>,
+    I2C: embedded_hal::i2c::I2c<Error = E>,
 {
     /// Turn power on.
-    pub fn enable(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_enable(Enable::PON, true)
+    pub async fn enable(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::PON, true).await
     }
 
     /// Deactivate everything and put the device to sleep.
-    pub fn disable(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_enable(Enable::ALL, false)
+    pub async fn disable(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::ALL, false).await
     }
 
     /// Enable the wait feature.
@@ -36,13 +82,13 @@ where
     /// The duration of the wait can be configured with
     /// [`set_wait_time()`](struct.Apds9960.html#method.set_wait_time) and
     /// [`enable_wait_long()`](struct.Apds9960.html#method.enable_wait_long).
-    pub fn enable_wait(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_enable(Enable::WEN, true)
+    pub async fn enable_wait(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::WEN, true).await
     }
 
     /// Disable the wait feature.
-    pub fn disable_wait(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_enable(Enable::WEN, false)
+    pub async fn disable_wait(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::WEN, false).await
     }
 
     /// Enable long wait.
@@ -51,13 +97,13 @@ where
     /// See also: [`set_wait_time()`](struct.Apds9960.html#method.set_wait_time).
     ///
     /// Wait must be enabled with [`enable_wait()`](struct.Apds9960.html#method.enable_wait).
-    pub fn enable_wait_long(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_config1(Config1::WLONG, true)
+    pub async fn enable_wait_long(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config1(Config1::WLONG, true).await
     }
 
     /// Disable long wait.
-    pub fn disable_wait_long(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_config1(Config1::WLONG, false)
+    pub async fn disable_wait_long(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config1(Config1::WLONG, false).await
     }
 
     /// Set the waiting time between proximity and / or color and ambient light cycles.
@@ -71,46 +117,220 @@ where
     ///
     /// Waiting must be enabled with [`enable_wait()`](struct.Apds9960.html#method.enable_wait).
     /// Long wait can be enabled with [`enable_wait_long()`](struct.Apds9960.html#method.enable_wait_long).
-    pub fn set_wait_time(&mut self, value: u8) -> Result<(), Error<E>> {
-        self.write_register(Register::WTIME, value)
+    ///
+    /// See also: [`set_wait_cycles()`](struct.Apds9960.html#method.set_wait_cycles)
+    /// and [`set_wait_duration()`](struct.Apds9960.html#method.set_wait_duration).
+    pub async fn set_wait_time(&mut self, value: u8) -> Result<(), Error<E>> {
+        self.write_register(Register::WTIME, value).await
+    }
+
+    /// Set the waiting time as a number of cycles.
+    ///
+    /// Each cycle has a fixed duration of 2.78ms except if long wait is enabled,
+    /// then this time is multiplied by 12.
+    /// Valid values are in the range `[1-256]`, otherwise `Error::InvalidInputData` is returned.
+    pub async fn set_wait_cycles(&mut self, cycles: u16) -> Result<(), Error<E>> {
+        if cycles == 0 || cycles > 256 {
+            return Err(Error::InvalidInputData);
+        }
+        self.set_wait_time((256 - cycles) as u8).await
+    }
+
+    /// Set the waiting time as a duration.
+    ///
+    /// The duration is rounded to the nearest number of cycles (at least 1) and
+    /// the waiting time actually achieved is returned.
+    /// Long wait is enabled automatically if this achieves a waiting time closer to the
+    /// duration than with at most 256 cycles of 2.78ms (711.68ms), and disabled otherwise.
+    ///
+    /// Durations longer than 256 long wait cycles (8.54s) return `Error::InvalidInputData`.
+    pub async fn set_wait_duration(&mut self, duration: Duration) -> Result<Duration, Error<E>> {
+        let short_cycles = duration_to_cycles(duration, CYCLE_US).unwrap_or(256);
+        let long_cycle_us = 12 * CYCLE_US;
+        let long_cycles =
+            duration_to_cycles(duration, long_cycle_us).ok_or(Error::InvalidInputData)?;
+        let error = |cycles, cycle_us| {
+            let achieved = cycles_to_duration(cycles, cycle_us);
+            achieved.as_micros().abs_diff(duration.as_micros())
+        };
+        let (cycles, cycle_us) =
+            if error(short_cycles, CYCLE_US) <= error(long_cycles, long_cycle_us) {
+                (short_cycles, CYCLE_US)
+            } else {
+                (long_cycles, long_cycle_us)
+            };
+        // WLONG is set first so that the new number of cycles is never used with the old scale.
+        self.set_flag_config1(Config1::WLONG, cycle_us != CYCLE_US)
+            .await?;
+        self.set_wait_cycles(cycles).await?;
+        Ok(cycles_to_duration(cycles, cycle_us))
+    }
+
+    /// Enable sleep after interrupt.
+    ///
+    /// The device will go into low power mode after an interrupt is asserted.
+    /// Operation will resume once the interrupt is cleared.
+    pub async fn enable_sleep_after_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::SAI, true).await
+    }
+
+    /// Disable sleep after interrupt.
+    pub async fn disable_sleep_after_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::SAI, false).await
     }
 
     /// Force an interrupt.
-    pub fn force_interrupt(&mut self) -> Result<(), Error<E>> {
-        self.touch_register(Register::IFORCE)
+    pub async fn force_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.touch_register(Register::IFORCE).await
     }
 
     /// Clear all *non-gesture* interrupts.
-    pub fn clear_interrupts(&mut self) -> Result<(), Error<E>> {
-        self.touch_register(Register::AICLEAR)
+    pub async fn clear_interrupts(&mut self) -> Result<(), Error<E>> {
+        self.touch_register(Register::AICLEAR).await
+    }
+
+    /// Reset the device configuration to the power-on default values.
+    ///
+    /// The device has no reset command so this writes the power-on default value
+    /// documented in the datasheet to every writable register, including those
+    /// for which there is no method in this driver.
+    /// The device is disabled, all interrupts are cleared and the gesture FIFO is emptied.
+    /// The cached register state of the driver is reset accordingly.
+    pub async fn reset_to_defaults(&mut self) -> Result<(), Error<E>> {
+        // Contiguous registers are written at once. Reserved addresses are skipped.
+        let defaults: [&[u8]; 9] = [
+            &[Register::ENABLE, 0],
+            &[Register::ATIME, ATime::default().value()],
+            // WTIME, AILTL, AILTH, AIHTL, AIHTH
+            &[Register::WTIME, 0xFF, 0, 0, 0, 0],
+            &[Register::PILT, 0],
+            // PIHT, PERS, CONFIG1, PPULSE, CONTROL, CONFIG2
+            &[
+                Register::PIHT,
+                0,
+                Pers::default().value(),
+                Config1::default().value(),
+                PPulse::default().value(),
+                Control::default().value(),
+                Config2::default().value(),
+            ],
+            // POFFSET_UR, POFFSET_DL, CONFIG3, GPENTH, GEXTH, GCONFIG1, GCONFIG2
+            &[
+                Register::POFFSET_UR,
+                0,
+                0,
+                Config3::default().value(),
+                0,
+                0,
+                GConfig1::default().value(),
+                GConfig2::default().value(),
+            ],
+            // GOFFSET_U, GOFFSET_D, GPULSE, GOFFSET_L
+            &[Register::GOFFSET_U, 0, 0, GPulse::default().value(), 0],
+            // GOFFSET_R, GCONFIG3, GCONFIG4 (clearing the gesture FIFO)
+            &[
+                Register::GOFFSET_R,
+                0,
+                GConfig3::default().value(),
+                GConfig4::default().with(GConfig4::GFIFO_CLR, true).value(),
+            ],
+            &[Register::AICLEAR],
+        ];
+        for data in defaults.iter() {
+            self.i2c.write(DEV_ADDR, data).await.map_err(Error::I2C)?;
+        }
+        self.enable = Enable::default();
+        self.atime = ATime::default();
+        self.pers = Pers::default();
+        self.config1 = Config1::default();
+        self.ppulse = PPulse::default();
+        self.control = Control::default();
+        self.config2 = Config2::default();
+        self.config3 = Config3::default();
+        self.gconfig1 = GConfig1::default();
+        self.gconfig2 = GConfig2::default();
+        self.gpulse = GPulse::default();
+        self.gconfig3 = GConfig3::default();
+        self.gconfig4 = GConfig4::default();
+        Ok(())
     }
 
-    impl_set_flag_reg!(set_flag_enable, enable);
-    impl_set_flag_reg!(set_flag_config1, config1);
-    impl_set_flag_reg!(set_flag_config2, config2);
-    impl_set_flag_reg!(set_flag_gconfig4, gconfig4);
+    pub(crate) async fn set_flag_enable(&mut self, flag: u8, value: bool) -> Result<(), Error<E>> {
+        let new = self.enable.with(flag, value);
+        self.config_register(&new).await?;
+        self.enable = new;
+        Ok(())
+    }
+
+    pub(crate) async fn set_flag_config1(&mut self, flag: u8, value: bool) -> Result<(), Error<E>> {
+        let new = self.config1.with(flag, value);
+        self.config_register(&new).await?;
+        self.config1 = new;
+        Ok(())
+    }
 
-    pub(crate) fn config_register<T: BitFlags>(&mut self, reg: &T) -> Result<(), Error<E>> {
-        self.write_register(T::ADDRESS, reg.value())
+    pub(crate) async fn set_flag_config2(&mut self, flag: u8, value: bool) -> Result<(), Error<E>> {
+        let new = self.config2.with(flag, value);
+        self.config_register(&new).await?;
+        self.config2 = new;
+        Ok(())
     }
 
-    pub(crate) fn write_register(&mut self, address: u8, value: u8) -> Result<(), Error<E>> {
+    pub(crate) async fn set_flag_config3(&mut self, flag: u8, value: bool) -> Result<(), Error<E>> {
+        let new = self.config3.with(flag, value);
+        self.config_register(&new).await?;
+        self.config3 = new;
+        Ok(())
+    }
+
+    pub(crate) async fn set_flag_gconfig1(
+        &mut self,
+        flag: u8,
+        value: bool,
+    ) -> Result<(), Error<E>> {
+        let new = self.gconfig1.with(flag, value);
+        self.config_register(&new).await?;
+        self.gconfig1 = new;
+        Ok(())
+    }
+
+    pub(crate) async fn set_flag_gconfig4(
+        &mut self,
+        flag: u8,
+        value: bool,
+    ) -> Result<(), Error<E>> {
+        let new = self.gconfig4.with(flag, value);
+        self.config_register(&new).await?;
+        self.gconfig4 = new;
+        Ok(())
+    }
+
+    pub(crate) async fn config_register<T: BitFlags>(&mut self, reg: &T) -> Result<(), Error<E>> {
+        self.write_register(T::ADDRESS, reg.value()).await
+    }
+
+    pub(crate) async fn write_register(&mut self, address: u8, value: u8) -> Result<(), Error<E>> {
         self.i2c
             .write(DEV_ADDR, &[address, value])
+            .await
             .map_err(Error::I2C)
     }
 
-    pub(crate) fn write_double_register(
+    pub(crate) async fn write_double_register(
         &mut self,
         start_register: u8,
         value: u16,
     ) -> Result<(), Error<E>> {
         self.i2c
             .write(DEV_ADDR, &[start_register, value as u8, (value >> 8) as u8])
+            .await
             .map_err(Error::I2C)
     }
 
-    pub(crate) fn touch_register(&mut self, address: u8) -> Result<(), Error<E>> {
-        self.i2c.write(DEV_ADDR, &[address]).map_err(Error::I2C)
+    pub(crate) async fn touch_register(&mut self, address: u8) -> Result<(), Error<E>> {
+        self.i2c
+            .write(DEV_ADDR, &[address])
+            .await
+            .map_err(Error::I2C)
     }
 }
@@ -0,0 +1,138 @@
+#[cfg(feature = "async")]
+use crate::Apds9960Async;
+use crate::{
+    calibration::{Baseline, OffsetSearch, MAX_POLLS},
+    config::register_to_offset,
+    register::{Enable, GConfig4},
+    Apds9960, BitFlags, Error, GestureCalibration, Register,
+};
+
+/// Gesture crosstalk calibration.
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async"),
+    idents(
+        Apds9960(sync, async = "Apds9960Async"),
+        embedded_hal(sync, async = "embedded_hal_async")
+    )
+)]
+impl<I2C, E> Apds9960<I2C>
+where
+    I2C: embedded_hal::i2c::I2c<Error = E>,
+{
+    /// Calibrate the gesture photodiode offsets to compensate the crosstalk.
+    ///
+    /// This works like [`calibrate_proximity_offsets()`](struct.Apds9960.html#method.calibrate_proximity_offsets)
+    /// but the device is kept in gesture mode and the up, down, left and right offsets are
+    /// searched at the same time from the datasets in the gesture FIFO.
+    /// Afterwards, the previous gesture configuration is restored and the gesture FIFO is cleared.
+    /// The gesture mode is read back from the device first so that it is not entered again
+    /// if the device has left it.
+    ///
+    /// The offsets can be set again later with
+    /// [`set_gesture_calibration()`](struct.Apds9960.html#method.set_gesture_calibration).
+    pub async fn calibrate_gesture_offsets(&mut self) -> Result<GestureCalibration, Error<E>> {
+        self.is_gesture_mode_enabled().await?;
+        let enable = self.enable.value();
+        let gconfig4 = self.gconfig4.value();
+        let mut offsets = [0; 4];
+        self.read_data(Register::GOFFSET_U, &mut offsets[..2])
+            .await?;
+        offsets[2] = self.read_register(Register::GOFFSET_L).await?;
+        offsets[3] = self.read_register(Register::GOFFSET_R).await?;
+
+        let result = self.search_gesture_offsets().await;
+        let restored = self.restore_gesture_state(enable, gconfig4).await;
+        let calibration = match result {
+            Ok(calibration) => calibration,
+            Err(e) => {
+                // The error of the calibration is more relevant.
+                let [up, down, left, right] = offsets.map(register_to_offset);
+                let _ = self.set_gesture_offsets(up, down, left, right).await;
+                return Err(e);
+            }
+        };
+        restored?;
+        self.set_gesture_calibration(calibration).await?;
+        Ok(calibration)
+    }
+
+    /// Set the gesture photodiode offsets from a previous calibration.
+    ///
+    /// See [`calibrate_gesture_offsets()`](struct.Apds9960.html#method.calibrate_gesture_offsets).
+    pub async fn set_gesture_calibration(
+        &mut self,
+        calibration: GestureCalibration,
+    ) -> Result<(), Error<E>> {
+        self.set_gesture_offsets(
+            calibration.up_offset,
+            calibration.down_offset,
+            calibration.left_offset,
+            calibration.right_offset,
+        )
+        .await
+    }
+
+    async fn search_gesture_offsets(&mut self) -> Result<GestureCalibration, Error<E>> {
+        let new = Enable::create(Enable::PON | Enable::PEN | Enable::GEN);
+        self.config_register(&new).await?;
+        self.enable = new;
+
+        let mut searches = [OffsetSearch::new(); 4];
+        while !searches.iter().all(OffsetSearch::is_done) {
+            let [up, down, left, right] = searches.map(|search| search.offset());
+            self.set_gesture_offsets(up, down, left, right).await?;
+            // Datasets measured with the previous offsets are discarded.
+            self.clear_gesture_fifo().await?;
+            let mut baseline = Baseline::new();
+            while !baseline.is_complete() {
+                baseline.add(self.read_gesture_dataset_blocking().await?);
+            }
+            for (search, value) in searches.iter_mut().zip(baseline.average().iter()) {
+                search.update(*value);
+            }
+        }
+        let [up_offset, down_offset, left_offset, right_offset] =
+            searches.map(|search| search.offset());
+        Ok(GestureCalibration {
+            up_offset,
+            down_offset,
+            left_offset,
+            right_offset,
+        })
+    }
+
+    /// Restore the gesture configuration, clearing the gesture FIFO, and the enabled functions.
+    /// Both are attempted even if one fails.
+    async fn restore_gesture_state(&mut self, enable: u8, gconfig4: u8) -> Result<(), Error<E>> {
+        let new = GConfig4::create(gconfig4);
+        let gconfig4 = self
+            .config_register(&new.with(GConfig4::GFIFO_CLR, true))
+            .await;
+        self.gconfig4 = new;
+        let new = Enable::create(enable);
+        self.config_register(&new).await?;
+        self.enable = new;
+        gconfig4
+    }
+
+    /// Read a single up, down, left and right dataset from the gesture FIFO.
+    ///
+    /// Gesture mode is entered again whenever the device has left it, since no target
+    /// is present during the calibration.
+    async fn read_gesture_dataset_blocking(&mut self) -> Result<[u8; 4], Error<E>> {
+        for _ in 0..MAX_POLLS {
+            match self.read_gesture_dataset().await {
+                Ok(dataset) => {
+                    return Ok([dataset.up, dataset.down, dataset.left, dataset.right]);
+                }
+                Err(nb::Error::WouldBlock) => (),
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+            if !self.is_gesture_mode_enabled().await? {
+                self.set_flag_gconfig4(GConfig4::GMODE, true).await?;
+            }
+        }
+        Err(Error::Timeout)
+    }
+}
@@ -1,121 +1,352 @@
-use hal::blocking::i2c;
-use {
-    register::{Enable, GConfig1, GConfig4},
-    Apds9960, BitFlags, Error, GestureDataThreshold, Register, DEV_ADDR,
+#[cfg(feature = "async")]
+use crate::Apds9960Async;
+use crate::{
+    config::offset_to_register,
+    register::{Enable, GConfig1, GConfig2, GConfig3, GConfig4, GPulse},
+    Apds9960, BitFlags, Error, GestureDataThreshold, GestureDimensions, GestureExitPersistence,
+    GestureGain, GesturePulseLength, GestureWaitTime, LedDrive, Register, DEV_ADDR,
 };
 
 /// Gesture engine configuration.
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async"),
+    idents(
+        Apds9960(sync, async = "Apds9960Async"),
+        embedded_hal(sync, async = "embedded_hal_async")
+    )
+)]
 impl<I2C, E> Apds9960<I2C>
 where
-    I2C: i2c::Write<Error = E>,
+    I2C: embedded_hal::i2c::I2c<Error = E>,
 {
     /// Enable gesture detection
-    pub fn enable_gesture(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_enable(Enable::GEN, true)
+    pub async fn enable_gesture(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::GEN, true).await
     }
 
     /// Disable gesture detection
-    pub fn disable_gesture(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_enable(Enable::GEN, false)
+    pub async fn disable_gesture(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::GEN, false).await
     }
 
     /// Enable gesture mode.
     ///
     /// This can be automatically enabled (depending on proximity thresholds)
     /// and disabled (see GMODE on datasheet).
-    pub fn enable_gesture_mode(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_gconfig4(GConfig4::GMODE, true)
+    pub async fn enable_gesture_mode(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig4(GConfig4::GMODE, true).await
     }
 
     /// Disable gesture mode.
     ///
     /// This can be automatically enabled (depending on proximity thresholds)
     /// and disabled (see GMODE on datasheet).
-    pub fn disable_gesture_mode(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_gconfig4(GConfig4::GMODE, false)
+    pub async fn disable_gesture_mode(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig4(GConfig4::GMODE, false).await
     }
 
     /// Enable gesture interrupt generation
-    pub fn enable_gesture_interrupts(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_gconfig4(GConfig4::GIEN, true)
+    pub async fn enable_gesture_interrupts(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig4(GConfig4::GIEN, true).await
     }
 
     /// Disable gesture interrupt generation
-    pub fn disable_gesture_interrupts(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_gconfig4(GConfig4::GIEN, false)
+    pub async fn disable_gesture_interrupts(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig4(GConfig4::GIEN, false).await
+    }
+
+    /// Clear the gesture FIFO.
+    ///
+    /// This discards all the data in the gesture FIFO and clears the gesture interrupt
+    /// as well as the gesture data valid and overflow flags.
+    /// Use this to recover after the gesture data has overflown.
+    ///
+    /// The gesture mode is read back from the device first since the device leaves
+    /// gesture mode by itself and it must not be entered again.
+    pub async fn clear_gesture_fifo(&mut self) -> Result<(), Error<E>> {
+        self.is_gesture_mode_enabled().await?;
+        // GFIFO_CLR is cleared by the device itself so it is not kept.
+        let reg = self.gconfig4.with(GConfig4::GFIFO_CLR, true);
+        self.config_register(&reg).await
+    }
+
+    /// Clear the gesture interrupt.
+    ///
+    /// The gesture interrupt can only be cleared by emptying the gesture FIFO so
+    /// any data remaining in it will be discarded.
+    /// See [`clear_gesture_fifo()`](struct.Apds9960.html#method.clear_gesture_fifo).
+    pub async fn clear_gesture_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.clear_gesture_fifo().await
     }
 
     /// Set the threshold of amount of available data in the gesture FIFO registers.
-    pub fn set_gesture_data_level_threshold(
+    pub async fn set_gesture_data_level_threshold(
         &mut self,
         threshold: GestureDataThreshold,
     ) -> Result<(), Error<E>> {
         use GestureDataThreshold as GDTH;
-        let flags;
-        match threshold {
-            GDTH::Th1 => flags = (false, false),
-            GDTH::Th4 => flags = (false, true),
-            GDTH::Th8 => flags = (true, false),
-            GDTH::Th16 => flags = (true, true),
-        }
+        let flags = match threshold {
+            GDTH::Th1 => (false, false),
+            GDTH::Th4 => (false, true),
+            GDTH::Th8 => (true, false),
+            GDTH::Th16 => (true, true),
+        };
         let new = self
             .gconfig1
             .with(GConfig1::GFIFOTH1, flags.0)
             .with(GConfig1::GFIFOTH0, flags.1);
-        self.config_register(&new)?;
+        self.config_register(&new).await?;
+        self.gconfig1 = new;
+        Ok(())
+    }
+
+    /// Enable the gesture exit up photodiode mask.
+    ///
+    /// The up photodiode will not be used to determine the end of a gesture.
+    pub async fn enable_gesture_exit_up_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig1(GConfig1::GEXMSK_U, true).await
+    }
+
+    /// Disable the gesture exit up photodiode mask.
+    pub async fn disable_gesture_exit_up_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig1(GConfig1::GEXMSK_U, false).await
+    }
+
+    /// Enable the gesture exit down photodiode mask.
+    ///
+    /// The down photodiode will not be used to determine the end of a gesture.
+    pub async fn enable_gesture_exit_down_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig1(GConfig1::GEXMSK_D, true).await
+    }
+
+    /// Disable the gesture exit down photodiode mask.
+    pub async fn disable_gesture_exit_down_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig1(GConfig1::GEXMSK_D, false).await
+    }
+
+    /// Enable the gesture exit left photodiode mask.
+    ///
+    /// The left photodiode will not be used to determine the end of a gesture.
+    pub async fn enable_gesture_exit_left_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig1(GConfig1::GEXMSK_L, true).await
+    }
+
+    /// Disable the gesture exit left photodiode mask.
+    pub async fn disable_gesture_exit_left_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig1(GConfig1::GEXMSK_L, false).await
+    }
+
+    /// Enable the gesture exit right photodiode mask.
+    ///
+    /// The right photodiode will not be used to determine the end of a gesture.
+    pub async fn enable_gesture_exit_right_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig1(GConfig1::GEXMSK_R, true).await
+    }
+
+    /// Disable the gesture exit right photodiode mask.
+    pub async fn disable_gesture_exit_right_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_gconfig1(GConfig1::GEXMSK_R, false).await
+    }
+
+    /// Set the gesture exit persistence.
+    ///
+    /// This is the number of consecutive gesture end occurrences required to exit gesture mode.
+    pub async fn set_gesture_exit_persistence(
+        &mut self,
+        persistence: GestureExitPersistence,
+    ) -> Result<(), Error<E>> {
+        let flags = match persistence {
+            GestureExitPersistence::First => (false, false),
+            GestureExitPersistence::Second => (false, true),
+            GestureExitPersistence::Fourth => (true, false),
+            GestureExitPersistence::Seventh => (true, true),
+        };
+        let new = self
+            .gconfig1
+            .with(GConfig1::GEXPERS1, flags.0)
+            .with(GConfig1::GEXPERS0, flags.1);
+        self.config_register(&new).await?;
         self.gconfig1 = new;
         Ok(())
     }
 
+    /// Set the gesture gain.
+    pub async fn set_gesture_gain(&mut self, gain: GestureGain) -> Result<(), Error<E>> {
+        let flags = match gain {
+            GestureGain::X1 => (false, false),
+            GestureGain::X2 => (false, true),
+            GestureGain::X4 => (true, false),
+            GestureGain::X8 => (true, true),
+        };
+        let new = self
+            .gconfig2
+            .with(GConfig2::GGAIN1, flags.0)
+            .with(GConfig2::GGAIN0, flags.1);
+        self.config_register(&new).await?;
+        self.gconfig2 = new;
+        Ok(())
+    }
+
+    /// Set the gesture LED drive strength.
+    pub async fn set_gesture_led_drive(&mut self, drive: LedDrive) -> Result<(), Error<E>> {
+        let flags = match drive {
+            LedDrive::Ma100 => (false, false),
+            LedDrive::Ma50 => (false, true),
+            LedDrive::Ma25 => (true, false),
+            LedDrive::Ma12_5 => (true, true),
+        };
+        let new = self
+            .gconfig2
+            .with(GConfig2::GLDRIVE1, flags.0)
+            .with(GConfig2::GLDRIVE0, flags.1);
+        self.config_register(&new).await?;
+        self.gconfig2 = new;
+        Ok(())
+    }
+
+    /// Set the gesture wait time between gesture detection cycles.
+    pub async fn set_gesture_wait_time(&mut self, time: GestureWaitTime) -> Result<(), Error<E>> {
+        let value = match time {
+            GestureWaitTime::Ms0 => 0,
+            GestureWaitTime::Ms2_8 => 1,
+            GestureWaitTime::Ms5_6 => 2,
+            GestureWaitTime::Ms8_4 => 3,
+            GestureWaitTime::Ms14 => 4,
+            GestureWaitTime::Ms22_4 => 5,
+            GestureWaitTime::Ms30_8 => 6,
+            GestureWaitTime::Ms39_2 => 7,
+        };
+        let new = self.gconfig2.with_bits(GConfig2::GWTIME, value);
+        self.config_register(&new).await?;
+        self.gconfig2 = new;
+        Ok(())
+    }
+
+    /// Set the photodiode pairs used for gesture detection.
+    pub async fn set_gesture_dimensions(
+        &mut self,
+        dimensions: GestureDimensions,
+    ) -> Result<(), Error<E>> {
+        let flags = match dimensions {
+            GestureDimensions::All => (false, false),
+            GestureDimensions::UpDown => (false, true),
+            GestureDimensions::LeftRight => (true, false),
+        };
+        let new = self
+            .gconfig3
+            .with(GConfig3::GDIMS1, flags.0)
+            .with(GConfig3::GDIMS0, flags.1);
+        self.config_register(&new).await?;
+        self.gconfig3 = new;
+        Ok(())
+    }
+
+    /// Set the gesture LED pulse length.
+    pub async fn set_gesture_pulse_length(
+        &mut self,
+        length: GesturePulseLength,
+    ) -> Result<(), Error<E>> {
+        let flags = match length {
+            GesturePulseLength::Us4 => (false, false),
+            GesturePulseLength::Us8 => (false, true),
+            GesturePulseLength::Us16 => (true, false),
+            GesturePulseLength::Us32 => (true, true),
+        };
+        let new = self
+            .gpulse
+            .with(GPulse::GPLEN1, flags.0)
+            .with(GPulse::GPLEN0, flags.1);
+        self.config_register(&new).await?;
+        self.gpulse = new;
+        Ok(())
+    }
+
+    /// Set the number of gesture LED pulses per gesture detection cycle.
+    ///
+    /// Valid values are `1` to `64`. Per default this is set to `1`.
+    ///
+    /// Returns `Error::InvalidInputData` if the count is out of range.
+    pub async fn set_gesture_pulse_count(&mut self, count: u8) -> Result<(), Error<E>> {
+        if count == 0 || count > 64 {
+            return Err(Error::InvalidInputData);
+        }
+        let new = self.gpulse.with_bits(GPulse::GPULSE, count - 1);
+        self.config_register(&new).await?;
+        self.gpulse = new;
+        Ok(())
+    }
+
     /// Set the gesture proximity entry threshold.
-    pub fn set_gesture_proximity_entry_threshold(&mut self, threshold: u8) -> Result<(), Error<E>> {
-        self.write_register(Register::GPENTH, threshold)
+    pub async fn set_gesture_proximity_entry_threshold(
+        &mut self,
+        threshold: u8,
+    ) -> Result<(), Error<E>> {
+        self.write_register(Register::GPENTH, threshold).await
     }
 
     /// Set the gesture proximity exit threshold.
-    pub fn set_gesture_proximity_exit_threshold(&mut self, threshold: u8) -> Result<(), Error<E>> {
-        self.write_register(Register::GPEXTH, threshold)
+    pub async fn set_gesture_proximity_exit_threshold(
+        &mut self,
+        threshold: u8,
+    ) -> Result<(), Error<E>> {
+        self.write_register(Register::GPEXTH, threshold).await
     }
 
     /// Set the gesture up offset.
-    pub fn set_gesture_up_offset(&mut self, offset: i8) -> Result<(), Error<E>> {
-        self.write_register(Register::GOFFSET_U, offset as u8)
+    ///
+    /// Valid values are in the range `[-127, 127]`, otherwise `Error::InvalidInputData` is returned.
+    pub async fn set_gesture_up_offset(&mut self, offset: i8) -> Result<(), Error<E>> {
+        let value = offset_to_register(offset).ok_or(Error::InvalidInputData)?;
+        self.write_register(Register::GOFFSET_U, value).await
     }
 
     /// Set the gesture down offset.
-    pub fn set_gesture_down_offset(&mut self, offset: i8) -> Result<(), Error<E>> {
-        self.write_register(Register::GOFFSET_D, offset as u8)
+    ///
+    /// Valid values are in the range `[-127, 127]`, otherwise `Error::InvalidInputData` is returned.
+    pub async fn set_gesture_down_offset(&mut self, offset: i8) -> Result<(), Error<E>> {
+        let value = offset_to_register(offset).ok_or(Error::InvalidInputData)?;
+        self.write_register(Register::GOFFSET_D, value).await
     }
 
     /// Set the gesture left offset.
-    pub fn set_gesture_left_offset(&mut self, offset: i8) -> Result<(), Error<E>> {
-        self.write_register(Register::GOFFSET_L, offset as u8)
+    ///
+    /// Valid values are in the range `[-127, 127]`, otherwise `Error::InvalidInputData` is returned.
+    pub async fn set_gesture_left_offset(&mut self, offset: i8) -> Result<(), Error<E>> {
+        let value = offset_to_register(offset).ok_or(Error::InvalidInputData)?;
+        self.write_register(Register::GOFFSET_L, value).await
     }
 
     /// Set the gesture right offset.
-    pub fn set_gesture_right_offset(&mut self, offset: i8) -> Result<(), Error<E>> {
-        self.write_register(Register::GOFFSET_R, offset as u8)
+    ///
+    /// Valid values are in the range `[-127, 127]`, otherwise `Error::InvalidInputData` is returned.
+    pub async fn set_gesture_right_offset(&mut self, offset: i8) -> Result<(), Error<E>> {
+        let value = offset_to_register(offset).ok_or(Error::InvalidInputData)?;
+        self.write_register(Register::GOFFSET_R, value).await
     }
 
     /// Set the gesture up, down, left and right offsets.
-    pub fn set_gesture_offsets(
+    ///
+    /// The left and right offset registers are not contiguous with the up and down
+    /// offset registers so this is done in several I²C transactions.
+    /// Valid values are in the range `[-127, 127]`, otherwise `Error::InvalidInputData` is returned
+    /// before anything is written.
+    pub async fn set_gesture_offsets(
         &mut self,
         offset_up: i8,
         offset_down: i8,
         offset_left: i8,
         offset_right: i8,
     ) -> Result<(), Error<E>> {
+        let up = offset_to_register(offset_up).ok_or(Error::InvalidInputData)?;
+        let down = offset_to_register(offset_down).ok_or(Error::InvalidInputData)?;
+        let left = offset_to_register(offset_left).ok_or(Error::InvalidInputData)?;
+        let right = offset_to_register(offset_right).ok_or(Error::InvalidInputData)?;
         self.i2c
-            .write(
-                DEV_ADDR,
-                &[
-                    Register::GOFFSET_U,
-                    offset_up as u8,
-                    offset_down as u8,
-                    offset_left as u8,
-                    offset_right as u8,
-                ],
-            )
-            .map_err(Error::I2C)
+            .write(DEV_ADDR, &[Register::GOFFSET_U, up, down])
+            .await
+            .map_err(Error::I2C)?;
+        self.write_register(Register::GOFFSET_L, left).await?;
+        self.write_register(Register::GOFFSET_R, right).await
     }
 }
@@ -1,2 +1,4 @@
+mod calibration;
 mod config;
 mod reading;
+mod recognizer;
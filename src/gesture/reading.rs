@@ -1,30 +1,55 @@
-use hal::blocking::i2c;
-use {register::GStatus, Apds9960, BitFlags, Error, Register};
+#[cfg(feature = "async")]
+use crate::Apds9960Async;
+use crate::{
+    register::{GConfig4, GStatus},
+    Apds9960, BitFlags, Error, GestureDataset, Register,
+};
 
 /// Gesture data reading.
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async"),
+    idents(
+        Apds9960(sync, async = "Apds9960Async"),
+        embedded_hal(sync, async = "embedded_hal_async")
+    )
+)]
 impl<I2C, E> Apds9960<I2C>
 where
-    I2C: i2c::WriteRead<Error = E>,
+    I2C: embedded_hal::i2c::I2c<Error = E>,
 {
     /// Read the amount of available data in the gesture FIFO registers.
-    pub fn read_gesture_data_level(&mut self) -> Result<u8, Error<E>> {
-        self.read_register(Register::GFLVL)
+    pub async fn read_gesture_data_level(&mut self) -> Result<u8, Error<E>> {
+        self.read_register(Register::GFLVL).await
     }
 
     /// Read whether there is valid gesture data available.
     #[allow(clippy::wrong_self_convention)]
-    pub fn is_gesture_data_valid(&mut self) -> Result<bool, Error<E>> {
-        let status = self.read_register(Register::GSTATUS)?;
+    pub async fn is_gesture_data_valid(&mut self) -> Result<bool, Error<E>> {
+        let status = self.read_register(Register::GSTATUS).await?;
         Ok(GStatus::create(status).is(GStatus::GVALID, true))
     }
 
     /// Read whether the gesture data has overflown.
     #[allow(clippy::wrong_self_convention)]
-    pub fn has_gesture_data_overflown(&mut self) -> Result<bool, Error<E>> {
-        let status = self.read_register(Register::GSTATUS)?;
+    pub async fn has_gesture_data_overflown(&mut self) -> Result<bool, Error<E>> {
+        let status = self.read_register(Register::GSTATUS).await?;
         Ok(GStatus::create(status).is(GStatus::GFOV, true))
     }
 
+    /// Read whether the device is currently in gesture mode.
+    ///
+    /// The device exits gesture mode by itself when the gesture exit conditions are met.
+    #[allow(clippy::wrong_self_convention)]
+    pub async fn is_gesture_mode_enabled(&mut self) -> Result<bool, Error<E>> {
+        let gconfig4 = GConfig4::create(self.read_register(Register::GCONFIG4).await?);
+        let gmode = gconfig4.is(GConfig4::GMODE, true);
+        // Keep the cached value in sync so that it is not enabled again
+        // on the next write.
+        self.gconfig4 = self.gconfig4.with(GConfig4::GMODE, gmode);
+        Ok(gmode)
+    }
+
     /// Read gesture data.
     ///
     /// Will read the gesture data up to the minimum of: gesture data level, array size.
@@ -36,14 +61,40 @@ where
     ///   up_dataset1, down_dataset1, left_dataset1, right_dataset1, ...]`
     ///
     /// Returns `nb::Error::WouldBlock` as long as not enough data is available.
-    pub fn read_gesture_data(&mut self, data: &mut [u8]) -> nb::Result<(), Error<E>> {
-        if !self.is_gesture_data_valid().map_err(nb::Error::Other)? {
+    pub async fn read_gesture_data(&mut self, data: &mut [u8]) -> nb::Result<(), Error<E>> {
+        if !self
+            .is_gesture_data_valid()
+            .await
+            .map_err(nb::Error::Other)?
+        {
             return Err(nb::Error::WouldBlock);
         }
-        let level = self.read_gesture_data_level().map_err(nb::Error::Other)?;
+        let level = self
+            .read_gesture_data_level()
+            .await
+            .map_err(nb::Error::Other)?;
         let byte_count = core::cmp::min(data.len(), 4 * level as usize);
         self.read_data(Register::GFIFO_U, &mut data[..byte_count])
+            .await
             .map_err(nb::Error::Other)?;
         Ok(())
     }
+
+    /// Read a single up, down, left and right dataset from the gesture FIFO.
+    ///
+    /// Returns `nb::Error::WouldBlock` as long as no data is available.
+    pub async fn read_gesture_dataset(&mut self) -> nb::Result<GestureDataset, Error<E>> {
+        let level = self
+            .read_gesture_data_level()
+            .await
+            .map_err(nb::Error::Other)?;
+        if level == 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        let mut data = [0; 4];
+        self.read_data(Register::GFIFO_U, &mut data)
+            .await
+            .map_err(nb::Error::Other)?;
+        Ok(GestureDataset::from(data))
+    }
 }
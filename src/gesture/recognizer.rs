@@ -0,0 +1,297 @@
+use crate::{Gesture, GestureDataset, GestureOrientation, GestureRecognizer};
+
+/// Default noise threshold.
+const NOISE_THRESHOLD: u8 = 10;
+/// Default sensitivity.
+const SENSITIVITY: u8 = 50;
+/// Default depth threshold.
+const DEPTH_THRESHOLD: u8 = 50;
+/// Default depth duration.
+const DEPTH_DURATION: u16 = 10;
+/// Default hover threshold.
+const HOVER_THRESHOLD: u8 = 20;
+/// Default hover duration.
+const HOVER_DURATION: u16 = 100;
+
+impl GestureRecognizer {
+    /// Create a new gesture recognizer with the default settings.
+    ///
+    /// The default noise threshold is 10, the default sensitivity is 50, the default
+    /// depth threshold is 50% over 10 datasets and the default hover threshold is 20%
+    /// over 100 datasets.
+    pub fn new() -> Self {
+        GestureRecognizer {
+            orientation: GestureOrientation::Normal,
+            noise_threshold: NOISE_THRESHOLD,
+            sensitivity: SENSITIVITY,
+            depth_threshold: DEPTH_THRESHOLD,
+            depth_duration: DEPTH_DURATION,
+            hover_threshold: HOVER_THRESHOLD,
+            hover_duration: HOVER_DURATION,
+            entry: None,
+            exit: GestureDataset::default(),
+            datasets: 0,
+            energy_sum: 0,
+            weighted_energy_sum: 0,
+            hover_reference: 0,
+            hover_datasets: 0,
+            hovering: false,
+            proximity: None,
+        }
+    }
+
+    /// Set the orientation in which the device is mounted.
+    pub fn set_orientation(&mut self, orientation: GestureOrientation) {
+        self.orientation = orientation;
+    }
+
+    /// Set the noise threshold.
+    ///
+    /// Datasets in which no photodiode value exceeds this are not considered part of a gesture.
+    pub fn set_noise_threshold(&mut self, threshold: u8) {
+        self.noise_threshold = threshold;
+    }
+
+    /// Set the sensitivity.
+    ///
+    /// This is the minimum change of the up/down or left/right ratio between the gesture entry
+    /// and exit, in percent of the photodiode values, for a gesture to be recognized.
+    /// Lower values recognize weaker movements but also produce more false detections.
+    pub fn set_sensitivity(&mut self, sensitivity: u8) {
+        self.sensitivity = sensitivity;
+    }
+
+    /// Set the depth threshold.
+    ///
+    /// This is the minimum change of the total photodiode energy over a gesture, in percent of
+    /// its average, for a near or far gesture to be recognized.
+    pub fn set_depth_threshold(&mut self, threshold: u8) {
+        self.depth_threshold = threshold;
+    }
+
+    /// Set the depth duration.
+    ///
+    /// This is the minimum number of datasets a gesture must last for a near or far gesture
+    /// to be recognized.
+    pub fn set_depth_duration(&mut self, datasets: u16) {
+        self.depth_duration = datasets;
+    }
+
+    /// Set the hover threshold.
+    ///
+    /// This is the maximum change of the total photodiode energy, in percent, for the target
+    /// to be considered still.
+    pub fn set_hover_threshold(&mut self, threshold: u8) {
+        self.hover_threshold = threshold;
+    }
+
+    /// Set the hover duration.
+    ///
+    /// This is the number of datasets the target must be held still for a hover gesture
+    /// to be recognized.
+    pub fn set_hover_duration(&mut self, datasets: u16) {
+        self.hover_duration = datasets;
+    }
+
+    /// Process a proximity reading as returned by
+    /// [`read_proximity()`](struct.Apds9960.html#method.read_proximity).
+    ///
+    /// The last reading before a gesture starts and the last reading during the gesture are
+    /// compared in order to confirm near and far gestures.
+    pub fn process_proximity(&mut self, proximity: u8) {
+        self.proximity = match (self.entry, self.proximity) {
+            (Some(_), Some((first, _))) => Some((first, proximity)),
+            _ => Some((proximity, proximity)),
+        };
+    }
+
+    /// Process gesture data as returned by
+    /// [`read_gesture_data()`](struct.Apds9960.html#method.read_gesture_data).
+    ///
+    /// The data contains up, down, left and right datasets. Any incomplete dataset at the end
+    /// is ignored. Returns the gesture recognized if a gesture ended within the data.
+    /// Should several gestures end within the data, the last one is returned.
+    pub fn process(&mut self, data: &[u8]) -> Option<Gesture> {
+        let mut gesture = None;
+        for dataset in data.chunks_exact(4) {
+            let dataset = GestureDataset::from([dataset[0], dataset[1], dataset[2], dataset[3]]);
+            if let Some(recognized) = self.process_dataset(dataset) {
+                gesture = Some(recognized);
+            }
+        }
+        gesture
+    }
+
+    /// Process a single dataset as returned by
+    /// [`read_gesture_dataset()`](struct.Apds9960.html#method.read_gesture_dataset).
+    ///
+    /// Returns the gesture recognized if this dataset ends a gesture or
+    /// the target has been held still for the hover duration.
+    pub fn process_dataset(&mut self, dataset: GestureDataset) -> Option<Gesture> {
+        let values = [dataset.up, dataset.down, dataset.left, dataset.right];
+        if values.iter().any(|value| *value > self.noise_threshold) {
+            if self.entry.is_none() {
+                self.entry = Some(dataset);
+            }
+            self.exit = dataset;
+            let energy: u16 = values.iter().map(|value| u16::from(*value)).sum();
+            // No depth gesture is recognized after a hover, so the trend is not needed
+            // anymore and the session may last indefinitely.
+            if !self.hovering {
+                self.energy_sum = self.energy_sum.saturating_add(i64::from(energy));
+                self.weighted_energy_sum = self
+                    .weighted_energy_sum
+                    .saturating_add(i64::from(self.datasets) * i64::from(energy));
+                self.datasets = self.datasets.saturating_add(1);
+            }
+            self.update_hover(energy)
+        } else {
+            self.finish()
+        }
+    }
+
+    /// End the current gesture and return the gesture recognized, if any.
+    ///
+    /// Call this when the device has left gesture mode, since the last datasets
+    /// in the FIFO may still exceed the noise threshold.
+    pub fn finish(&mut self) -> Option<Gesture> {
+        let entry = self.entry?;
+        let gesture = if self.hovering {
+            None
+        } else {
+            self.direction(entry).or_else(|| self.depth())
+        };
+        self.reset();
+        gesture
+    }
+
+    /// Discard the current gesture.
+    pub fn reset(&mut self) {
+        self.entry = None;
+        self.datasets = 0;
+        self.energy_sum = 0;
+        self.weighted_energy_sum = 0;
+        self.hover_datasets = 0;
+        self.hovering = false;
+        self.proximity = self.proximity.map(|(_, last)| (last, last));
+    }
+
+    fn direction(&self, entry: GestureDataset) -> Option<Gesture> {
+        let up_down = ratio(self.exit.up, self.exit.down) - ratio(entry.up, entry.down);
+        let left_right = ratio(self.exit.left, self.exit.right) - ratio(entry.left, entry.right);
+        let sensitivity = i16::from(self.sensitivity);
+        let gesture = if up_down.abs() >= left_right.abs() {
+            if up_down <= -sensitivity {
+                Gesture::Up
+            } else if up_down >= sensitivity {
+                Gesture::Down
+            } else {
+                return None;
+            }
+        } else if left_right <= -sensitivity {
+            Gesture::Left
+        } else if left_right >= sensitivity {
+            Gesture::Right
+        } else {
+            return None;
+        };
+        Some(rotate(gesture, self.orientation))
+    }
+
+    fn depth(&self) -> Option<Gesture> {
+        if self.datasets < u32::from(self.depth_duration) {
+            return None;
+        }
+        let trend = self.energy_trend();
+        let threshold = i64::from(self.depth_threshold);
+        let proximity_change = self
+            .proximity
+            .map_or(0, |(first, last)| i16::from(last) - i16::from(first));
+        if trend >= threshold && proximity_change >= 0 {
+            Some(Gesture::Near)
+        } else if trend <= -threshold && proximity_change <= 0 {
+            Some(Gesture::Far)
+        } else {
+            None
+        }
+    }
+
+    /// Change of the total energy over the gesture in percent of its average.
+    ///
+    /// This is computed from the slope of a least squares linear fit so that
+    /// no datasets need to be stored.
+    fn energy_trend(&self) -> i64 {
+        let n = i128::from(self.datasets);
+        let energy_sum = i128::from(self.energy_sum);
+        let denominator = n * (n + 1) * energy_sum;
+        if denominator == 0 {
+            return 0;
+        }
+        let index_sum = n * (n - 1) / 2;
+        let covariance = n * i128::from(self.weighted_energy_sum) - index_sum * energy_sum;
+        (1200 * covariance / denominator) as i64
+    }
+
+    fn update_hover(&mut self, energy: u16) -> Option<Gesture> {
+        let reference = u32::from(self.hover_reference);
+        let change = u32::from(energy.abs_diff(self.hover_reference));
+        if self.hover_datasets == 0 || change * 100 > u32::from(self.hover_threshold) * reference {
+            self.hover_reference = energy;
+            self.hover_datasets = 1;
+        } else {
+            self.hover_datasets = self.hover_datasets.saturating_add(1);
+        }
+        if !self.hovering && self.hover_datasets >= self.hover_duration {
+            self.hovering = true;
+            Some(Gesture::Hover)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<[u8; 4]> for GestureDataset {
+    /// Create a dataset from the up, down, left and right values as stored in the gesture FIFO.
+    fn from(data: [u8; 4]) -> Self {
+        GestureDataset {
+            up: data[0],
+            down: data[1],
+            left: data[2],
+            right: data[3],
+        }
+    }
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        GestureRecognizer::new()
+    }
+}
+
+/// Difference between two photodiode values in percent of their sum.
+fn ratio(a: u8, b: u8) -> i16 {
+    let sum = i16::from(a) + i16::from(b);
+    if sum == 0 {
+        0
+    } else {
+        (i16::from(a) - i16::from(b)) * 100 / sum
+    }
+}
+
+/// Direction seen by the user for a gesture recognized by a device mounted in
+/// the given orientation.
+fn rotate(gesture: Gesture, orientation: GestureOrientation) -> Gesture {
+    let turns = match orientation {
+        GestureOrientation::Normal => 0,
+        GestureOrientation::Rotated90 => 1,
+        GestureOrientation::Rotated180 => 2,
+        GestureOrientation::Rotated270 => 3,
+    };
+    (0..turns).fold(gesture, |gesture, _| match gesture {
+        Gesture::Up => Gesture::Right,
+        Gesture::Right => Gesture::Down,
+        Gesture::Down => Gesture::Left,
+        Gesture::Left => Gesture::Up,
+        depth => depth,
+    })
+}
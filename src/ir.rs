@@ -0,0 +1,37 @@
+use crate::{IrCompensatedLightData, LightData};
+
+impl LightData {
+    /// Estimate the infrared component of the light: `(red + green + blue - clear) / 2`.
+    ///
+    /// The result is at least 0 and at most 65535.
+    pub fn ir(&self) -> u16 {
+        let sum = i32::from(self.red) + i32::from(self.green) + i32::from(self.blue)
+            - i32::from(self.clear);
+        (sum / 2).clamp(0, i32::from(u16::MAX)) as u16
+    }
+
+    /// Get the color / ambient light data with the infrared component removed.
+    pub fn ir_compensated(&self) -> IrCompensatedLightData {
+        let ir = self.ir();
+        IrCompensatedLightData {
+            clear: i32::from(self.clear) - i32::from(ir),
+            red: i32::from(self.red) - i32::from(ir),
+            green: i32::from(self.green) - i32::from(ir),
+            blue: i32::from(self.blue) - i32::from(ir),
+            ir,
+        }
+    }
+}
+
+impl From<LightData> for IrCompensatedLightData {
+    fn from(data: LightData) -> Self {
+        data.ir_compensated()
+    }
+}
+
+impl IrCompensatedLightData {
+    /// Whether the clear channel was saturated for the given maximum count.
+    pub(crate) fn is_saturated(&self, max_count: u16) -> bool {
+        self.clear + i32::from(self.ir) >= i32::from(max_count)
+    }
+}
@@ -8,9 +8,15 @@
 //! - Enable/disable delay between proximity and / or color / ambient light cycles. See: [`enable_wait()`].
 //! - Enable/disable long delay between proximity and / or color / ambient light cycles. See: [`enable_wait_long()`].
 //! - Set the waiting time between proximity and / or color / ambient light cycles. See: [`set_wait_time()`].
+//! - Set the waiting time as a duration or number of cycles. See: [`set_wait_duration()`].
 //! - Force an interrupt. See: [`force_interrupt()`].
 //! - Clear all non-gesture interrupts. See: [`clear_interrupts()`].
+//! - Enable/disable sleep after interrupt. See: [`enable_sleep_after_interrupt()`].
 //! - Read the device ID. See: [`read_device_id()`].
+//! - Verify the device ID and identify the device variant. See: [`probe()`].
+//! - Read the device status. See: [`read_status()`].
+//! - Read the configuration back from the device. See: [`sync_from_device()`].
+//! - Reset the device configuration to the power-on defaults. See: [`reset_to_defaults()`].
 //! - Proximity:
 //!     - Enable/disable the proximity sensor. See: [`enable_proximity()`].
 //!     - Enable/disable proximity interrupt generation. See: [`enable_proximity_interrupts()`].
@@ -18,7 +24,15 @@
 //!     - Read the proximity data. See: [`read_proximity()`].
 //!     - Check whether the proximity data is valid. See: [`is_proximity_data_valid()`].
 //!     - Set the proximity interrupt low/high thresholds. See: [`set_proximity_low_threshold()`].
+//!     - Set the proximity interrupt persistence. See: [`set_proximity_interrupt_persistence()`].
 //!     - Set the proximity offsets. See: [`set_proximity_offsets()`].
+//!     - Calibrate the proximity offsets to compensate the crosstalk. See: [`calibrate_proximity_offsets()`].
+//!     - Enable/disable proximity gain compensation. See: [`enable_proximity_gain_compensation()`].
+//!     - Enable/disable the proximity photodiode masks. See: [`enable_proximity_up_mask()`].
+//!     - Set the LED drive strength. See: [`set_led_drive()`].
+//!     - Set the LED boost. See: [`set_led_boost()`].
+//!     - Set the proximity gain. See: [`set_proximity_gain()`].
+//!     - Set the proximity LED pulse length/count. See: [`set_proximity_pulse_length()`].
 //!     - Clear proximity interrupt. See: [`clear_proximity_interrupt()`].
 //! - Color / ambient light:
 //!     - Enable/disable the color / ambient light sensor. See: [`enable_light()`].
@@ -27,26 +41,52 @@
 //!     - Check whether the color / ambient light data is valid. See: [`is_light_data_valid()`].
 //!     - Read the color / ambient light data. See: [`read_light()`].
 //!     - Set the color / ambient light integration time. See: [`set_light_integration_time()`].
+//!     - Set the color / ambient light integration time as a duration or number of cycles. See: [`set_light_integration_duration()`].
+//!     - Get the maximum color / ambient light count for the integration time. See: [`max_light_count()`].
+//!     - Get the color / ambient light integration time and gain settings. See: [`light_settings()`].
+//!     - Set the color / ambient light integration time and gain at once. See: [`set_light_settings()`].
+//!     - Compute the illuminance from the color / ambient light data. See: [`LightData::millilux()`].
+//!     - Compute the correlated color temperature. See: [`LightData::cct()`].
+//!     - Compute the CIE 1931 XYZ values and xy chromaticity coordinates. See: [`color`].
+//!     - Estimate the infrared component and remove it from the color / ambient light data. See: [`LightData::ir_compensated()`].
+//!     - Adjust the color / ambient light gain and integration time automatically. See: [`LightAutoRange`].
+//!     - Set the color / ambient light gain. See: [`set_light_gain()`].
 //!     - Set the clear light channel interrupt low/high thresholds. See: [`set_light_low_threshold()`].
+//!     - Set the clear light channel interrupt persistence. See: [`set_light_interrupt_persistence()`].
 //!     - Clear ambient light interrupt. See: [`clear_light_interrupt()`].
 //! - Gesture detection:
 //!     - Enable/disable gesture detection. See: [`enable_gesture()`].
 //!     - Enable/disable gesture mode. See: [`enable_gesture_mode()`].
+//!     - Read whether the device is in gesture mode. See: [`is_gesture_mode_enabled()`].
 //!     - Enable/disable gesture interrupts. See: [`enable_gesture_interrupts()`].
 //!     - Read whether there is valid gesture data available. See: [`is_gesture_data_valid()`].
 //!     - Read the amount of gesture data available. See: [`read_gesture_data_level()`].
 //!     - Set the threshold of amount of available gesture data. See: [`set_gesture_data_level_threshold()`].
+//!     - Enable/disable the gesture exit photodiode masks. See: [`enable_gesture_exit_up_mask()`].
+//!     - Set the gesture exit persistence. See: [`set_gesture_exit_persistence()`].
+//!     - Set the gesture gain. See: [`set_gesture_gain()`].
+//!     - Set the gesture LED drive strength. See: [`set_gesture_led_drive()`].
+//!     - Set the gesture wait time. See: [`set_gesture_wait_time()`].
+//!     - Set the gesture dimensions. See: [`set_gesture_dimensions()`].
+//!     - Set the gesture LED pulse length/count. See: [`set_gesture_pulse_length()`].
 //!     - Read whether the gesture data has overflown. See: [`has_gesture_data_overflown()`].
+//!     - Clear the gesture FIFO and the gesture interrupt. See: [`clear_gesture_fifo()`].
 //!     - Read the gesture data. See: [`read_gesture_data()`].
+//!     - Read a single gesture dataset. See: [`read_gesture_dataset()`].
 //!     - Set the gesture proximity entry/exit thresholds. See: [`set_gesture_proximity_entry_threshold()`].
 //!     - Set the gesture offsets. See: [`set_gesture_offsets()`].
+//!     - Calibrate the gesture offsets to compensate the crosstalk. See: [`calibrate_gesture_offsets()`].
+//!     - Recognize up, down, left and right gestures from the gesture data. See: [`GestureRecognizer`].
+//!     - Recognize near, far and hover gestures from the gesture and proximity data. See: [`GestureRecognizer`].
 //!
 //! [`enable()`]: struct.Apds9960.html#method.enable
 //! [`enable_wait()`]: struct.Apds9960.html#method.enable_wait
 //! [`enable_wait_long()`]: struct.Apds9960.html#method.enable_wait_long
 //! [`set_wait_time()`]: struct.Apds9960.html#method.set_wait_time
+//! [`set_wait_duration()`]: struct.Apds9960.html#method.set_wait_duration
 //! [`force_interrupt()`]: struct.Apds9960.html#method.force_interrupt
 //! [`clear_interrupts()`]: struct.Apds9960.html#method.clear_interrupts
+//! [`enable_sleep_after_interrupt()`]: struct.Apds9960.html#method.enable_sleep_after_interrupt
 //!
 //! [`enable_proximity()`]: struct.Apds9960.html#method.enable_proximity
 //! [`enable_proximity_interrupts()`]: struct.Apds9960.html#method.enable_proximity_interrupts
@@ -54,7 +94,15 @@
 //! [`read_proximity()`]: struct.Apds9960.html#method.read_proximity
 //! [`is_proximity_data_valid()`]: struct.Apds9960.html#method.is_proximity_data_valid
 //! [`set_proximity_low_threshold()`]: struct.Apds9960.html#method.set_proximity_low_threshold()
+//! [`set_proximity_interrupt_persistence()`]: struct.Apds9960.html#method.set_proximity_interrupt_persistence
 //! [`set_proximity_offsets()`]: struct.Apds9960.html#method.set_proximity_offsets
+//! [`calibrate_proximity_offsets()`]: struct.Apds9960.html#method.calibrate_proximity_offsets
+//! [`enable_proximity_gain_compensation()`]: struct.Apds9960.html#method.enable_proximity_gain_compensation
+//! [`enable_proximity_up_mask()`]: struct.Apds9960.html#method.enable_proximity_up_mask
+//! [`set_led_drive()`]: struct.Apds9960.html#method.set_led_drive
+//! [`set_led_boost()`]: struct.Apds9960.html#method.set_led_boost
+//! [`set_proximity_gain()`]: struct.Apds9960.html#method.set_proximity_gain
+//! [`set_proximity_pulse_length()`]: struct.Apds9960.html#method.set_proximity_pulse_length
 //! [`clear_proximity_interrupt()`]: struct.Apds9960.html#method.clear_proximity_interrupt
 //!
 //! [`enable_light()`]: struct.Apds9960.html#method.enable_light
@@ -63,20 +111,63 @@
 //! [`is_light_data_valid()`]: struct.Apds9960.html#method.is_light_data_valid
 //! [`read_light()`]: struct.Apds9960.html#method.read_light
 //! [`set_light_integration_time()`]: struct.Apds9960.html#method.set_light_integration_time
+//! [`set_light_integration_duration()`]: struct.Apds9960.html#method.set_light_integration_duration
+//! [`max_light_count()`]: struct.Apds9960.html#method.max_light_count
+//! [`light_settings()`]: struct.Apds9960.html#method.light_settings
+//! [`set_light_settings()`]: struct.Apds9960.html#method.set_light_settings
+//! [`LightData::millilux()`]: struct.LightData.html#method.millilux
+//! [`LightData::cct()`]: struct.LightData.html#method.cct
+//! [`color`]: color/index.html
+//! [`LightData::ir_compensated()`]: struct.LightData.html#method.ir_compensated
+//! [`LightAutoRange`]: struct.LightAutoRange.html
+//! [`set_light_gain()`]: struct.Apds9960.html#method.set_light_gain
 //! [`set_light_low_threshold()`]: struct.Apds9960.html#method.set_light_low_threshold
+//! [`set_light_interrupt_persistence()`]: struct.Apds9960.html#method.set_light_interrupt_persistence
 //! [`clear_light_interrupt()`]: struct.Apds9960.html#method.clear_light_interrupt
 //!
 //! [`enable_gesture()`]: struct.Apds9960.html#method.enable_gesture
 //! [`enable_gesture_mode()`]: struct.Apds9960.html#method.enable_gesture_mode
+//! [`is_gesture_mode_enabled()`]: struct.Apds9960.html#method.is_gesture_mode_enabled
 //! [`enable_gesture_interrupts()`]: struct.Apds9960.html#method.enable_gesture_interrupts
 //! [`read_gesture_data_level()`]: struct.Apds9960.html#method.read_gesture_data_level
 //! [`set_gesture_data_level_threshold()`]: struct.Apds9960.html#method.set_gesture_data_level_threshold
+//! [`enable_gesture_exit_up_mask()`]: struct.Apds9960.html#method.enable_gesture_exit_up_mask
+//! [`set_gesture_exit_persistence()`]: struct.Apds9960.html#method.set_gesture_exit_persistence
+//! [`set_gesture_gain()`]: struct.Apds9960.html#method.set_gesture_gain
+//! [`set_gesture_led_drive()`]: struct.Apds9960.html#method.set_gesture_led_drive
+//! [`set_gesture_wait_time()`]: struct.Apds9960.html#method.set_gesture_wait_time
+//! [`set_gesture_dimensions()`]: struct.Apds9960.html#method.set_gesture_dimensions
+//! [`set_gesture_pulse_length()`]: struct.Apds9960.html#method.set_gesture_pulse_length
 //! [`read_gesture_data()`]: struct.Apds9960.html#method.read_gesture_data
+//! [`read_gesture_dataset()`]: struct.Apds9960.html#method.read_gesture_dataset
 //! [`is_gesture_data_valid()`]: struct.Apds9960.html#method.is_gesture_data_valid
 //! [`has_gesture_data_overflown()`]: struct.Apds9960.html#method.has_gesture_data_overflown
+//! [`clear_gesture_fifo()`]: struct.Apds9960.html#method.clear_gesture_fifo
 //! [`set_gesture_proximity_entry_threshold()`]: struct.Apds9960.html#method.set_gesture_proximity_entry_threshold
 //! [`set_gesture_offsets()`]: struct.Apds9960.html#method.set_gesture_offsets
+//! [`calibrate_gesture_offsets()`]: struct.Apds9960.html#method.calibrate_gesture_offsets
+//! [`GestureRecognizer`]: struct.GestureRecognizer.html
 //! [`read_device_id()`]: struct.Apds9960.html#method.read_device_id
+//! [`probe()`]: struct.Apds9960.html#method.probe
+//! [`read_status()`]: struct.Apds9960.html#method.read_status
+//! [`sync_from_device()`]: struct.Apds9960.html#method.sync_from_device
+//! [`reset_to_defaults()`]: struct.Apds9960.html#method.reset_to_defaults
+//!
+//! ## Async support
+//!
+//! Enabling the `async` feature makes `Apds9960Async` available. It offers the same API as
+//! `Apds9960` as `async` methods on top of the [`embedded-hal-async`] `I2c` trait.
+//! Both drivers are generated from the same source code so they share the register
+//! definitions and behavior.
+//! Likewise, `LightAutoRangeAsync` is the counterpart of [`LightAutoRange`] for `Apds9960Async`.
+//!
+//! The methods returning `nb::Result` do not await the data to be ready in `Apds9960Async`
+//! either. They return `nb::Error::WouldBlock` if no new data is available.
+//!
+//! The `async` feature requires Rust 1.75 or later, which is the minimum supported Rust
+//! version of [`embedded-hal-async`].
+//!
+//! [`embedded-hal-async`]: https://docs.rs/embedded-hal-async
 //!
 //! ## The device
 //!
@@ -100,13 +191,9 @@
 //! ### Read proximity
 //!
 //! ```no_run
-//! extern crate linux_embedded_hal as hal;
-//! #[macro_use]
-//! extern crate nb;
-//! extern crate apds9960;
-//!
-//! use hal::I2cdev;
 //! use apds9960::Apds9960;
+//! use linux_embedded_hal::I2cdev;
+//! use nb::block;
 //!
 //! # fn main() {
 //! let dev = I2cdev::new("/dev/i2c-1").unwrap();
@@ -123,13 +210,9 @@
 //! ### Read color / ambient light data
 //!
 //! ```no_run
-//! extern crate linux_embedded_hal as hal;
-//! #[macro_use]
-//! extern crate nb;
-//! extern crate apds9960;
-//!
-//! use hal::I2cdev;
 //! use apds9960::Apds9960;
+//! use linux_embedded_hal::I2cdev;
+//! use nb::block;
 //!
 //! # fn main() {
 //! let dev = I2cdev::new("/dev/i2c-1").unwrap();
@@ -152,13 +235,9 @@
 //! ### Read gesture data
 //!
 //! ```no_run
-//! extern crate linux_embedded_hal as hal;
-//! #[macro_use]
-//! extern crate nb;
-//! extern crate apds9960;
-//!
-//! use hal::I2cdev;
 //! use apds9960::Apds9960;
+//! use linux_embedded_hal::I2cdev;
+//! use nb::block;
 //!
 //! # fn main() {
 //! let dev = I2cdev::new("/dev/i2c-1").unwrap();
@@ -173,19 +252,55 @@
 //! }
 //! # }
 //! ```
+//!
+//! ### Read proximity asynchronously
+//!
+//! ```no_run
+//! # #[cfg(feature = "async")]
+//! # async fn run<I2C: embedded_hal_async::i2c::I2c>(i2c: I2C) {
+//! use apds9960::Apds9960Async;
+//!
+//! let mut sensor = Apds9960Async::new(i2c);
+//! sensor.enable().await.unwrap();
+//! sensor.enable_proximity().await.unwrap();
+//! loop {
+//!     if let Ok(prox) = sensor.read_proximity().await {
+//!         // use proximity data...
+//!     }
+//! }
+//! # }
+//! # fn main() {}
+//! ```
 
 #![deny(missing_docs, unsafe_code)]
 #![no_std]
 
-extern crate embedded_hal as hal;
-use hal::blocking::i2c;
-extern crate nb;
+use embedded_hal::i2c;
 
 /// All possible errors in this crate
 #[derive(Debug)]
 pub enum Error<E> {
     /// I²C bus error
     I2C(E),
+    /// Invalid input data provided
+    InvalidInputData,
+    /// The device ID read is not one of a known device variant
+    WrongDeviceId(u8),
+    /// The device did not provide new data in time
+    Timeout,
+}
+
+impl<E: i2c::Error> Error<E> {
+    /// Return the kind of the I²C bus error, if this is an I²C bus error.
+    ///
+    /// This allows telling apart e.g. a NACK from an arbitration loss
+    /// independently of the concrete HAL error type.
+    pub fn i2c_error_kind(&self) -> Option<i2c::ErrorKind> {
+        match self {
+            Error::I2C(e) => Some(e.kind()),
+            Error::InvalidInputData | Error::WrongDeviceId(_) | Error::Timeout => None,
+        }
+    }
 }
 
 /// Gesture FIFO data threshold.
@@ -203,6 +318,260 @@ pub enum GestureDataThreshold {
     Th16,
 }
 
+/// Gesture exit persistence.
+///
+/// Number of consecutive gesture end occurrences required to exit gesture mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureExitPersistence {
+    /// 1st gesture end occurrence results in exiting gesture mode. (default)
+    First,
+    /// 2nd gesture end occurrence results in exiting gesture mode.
+    Second,
+    /// 4th gesture end occurrence results in exiting gesture mode.
+    Fourth,
+    /// 7th gesture end occurrence results in exiting gesture mode.
+    Seventh,
+}
+
+/// Gesture gain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureGain {
+    /// 1x (default)
+    X1,
+    /// 2x
+    X2,
+    /// 4x
+    X4,
+    /// 8x
+    X8,
+}
+
+/// Gesture wait time between gesture detection cycles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureWaitTime {
+    /// 0 ms (default)
+    Ms0,
+    /// 2.8 ms
+    Ms2_8,
+    /// 5.6 ms
+    Ms5_6,
+    /// 8.4 ms
+    Ms8_4,
+    /// 14.0 ms
+    Ms14,
+    /// 22.4 ms
+    Ms22_4,
+    /// 30.8 ms
+    Ms30_8,
+    /// 39.2 ms
+    Ms39_2,
+}
+
+/// Gesture dimensions.
+///
+/// Selects which photodiode pairs are used for gesture detection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureDimensions {
+    /// Both up/down and left/right photodiode pairs are used. (default)
+    All,
+    /// Only the up/down photodiode pair is used.
+    UpDown,
+    /// Only the left/right photodiode pair is used.
+    LeftRight,
+}
+
+/// Gesture LED pulse length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GesturePulseLength {
+    /// 4 µs
+    Us4,
+    /// 8 µs (default)
+    Us8,
+    /// 16 µs
+    Us16,
+    /// 32 µs
+    Us32,
+}
+
+/// LED drive strength.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LedDrive {
+    /// 100 mA (default)
+    Ma100,
+    /// 50 mA
+    Ma50,
+    /// 25 mA
+    Ma25,
+    /// 12.5 mA
+    Ma12_5,
+}
+
+/// LED boost.
+///
+/// Additional LED current on top of the LED drive strength for proximity and gesture detection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LedBoost {
+    /// 100 % (default)
+    Percent100,
+    /// 150 %
+    Percent150,
+    /// 200 %
+    Percent200,
+    /// 300 %
+    Percent300,
+}
+
+/// Proximity LED pulse length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProximityPulseLength {
+    /// 4 µs
+    Us4,
+    /// 8 µs (default)
+    Us8,
+    /// 16 µs
+    Us16,
+    /// 32 µs
+    Us32,
+}
+
+/// Proximity gain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProximityGain {
+    /// 1x (default)
+    X1,
+    /// 2x
+    X2,
+    /// 4x
+    X4,
+    /// 8x
+    X8,
+}
+
+/// Color and ambient light gain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightGain {
+    /// 1x (default)
+    X1,
+    /// 4x
+    X4,
+    /// 16x
+    X16,
+    /// 64x
+    X64,
+}
+
+/// Device variant, as identified by its device ID.
+///
+/// Some boards are fitted with parts that are compatible with the APDS-9960
+/// but report a different device ID.
+/// No behavioral differences to the genuine device are currently known for these
+/// so the driver handles all variants in the same way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviceVariant {
+    /// Genuine APDS-9960 (ID `0xAB`)
+    Genuine,
+    /// Compatible device reporting ID `0xA8`
+    CloneA8,
+    /// Compatible device reporting ID `0x9C`
+    Clone9C,
+}
+
+/// Gesture FIFO dataset.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct GestureDataset {
+    /// Up photodiode value.
+    pub up: u8,
+    /// Down photodiode value.
+    pub down: u8,
+    /// Left photodiode value.
+    pub left: u8,
+    /// Right photodiode value.
+    pub right: u8,
+}
+
+/// Gesture recognized by a [`GestureRecognizer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    /// Movement in the up direction.
+    Up,
+    /// Movement in the down direction.
+    Down,
+    /// Movement in the left direction.
+    Left,
+    /// Movement in the right direction.
+    Right,
+    /// Movement toward the sensor.
+    Near,
+    /// Movement away from the sensor.
+    Far,
+    /// Target held still in front of the sensor.
+    Hover,
+}
+
+/// Orientation in which the device is mounted.
+///
+/// The rotation is clockwise, as seen from the front of the sensor.
+/// The gestures recognized by a [`GestureRecognizer`] are rotated accordingly so that
+/// they refer to the directions as seen by the user.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureOrientation {
+    /// Not rotated. (default)
+    Normal,
+    /// Rotated 90 degrees.
+    Rotated90,
+    /// Rotated 180 degrees.
+    Rotated180,
+    /// Rotated 270 degrees.
+    Rotated270,
+}
+
+/// Proximity photodiode offsets determined by the crosstalk calibration.
+///
+/// This can be stored and applied again later with
+/// [`set_proximity_calibration()`](struct.Apds9960.html#method.set_proximity_calibration).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProximityCalibration {
+    /// Up/right photodiode offset.
+    pub up_right_offset: i8,
+    /// Down/left photodiode offset.
+    pub down_left_offset: i8,
+}
+
+/// Gesture photodiode offsets determined by the crosstalk calibration.
+///
+/// This can be stored and applied again later with
+/// [`set_gesture_calibration()`](struct.Apds9960.html#method.set_gesture_calibration).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureCalibration {
+    /// Up photodiode offset.
+    pub up_offset: i8,
+    /// Down photodiode offset.
+    pub down_offset: i8,
+    /// Left photodiode offset.
+    pub left_offset: i8,
+    /// Right photodiode offset.
+    pub right_offset: i8,
+}
+
+/// Device status.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DeviceStatus {
+    /// Color / ambient light data is valid. (AVALID)
+    pub light_data_valid: bool,
+    /// Proximity data is valid. (PVALID)
+    pub proximity_data_valid: bool,
+    /// Gesture interrupt asserted. (GINT)
+    pub gesture_interrupt: bool,
+    /// Color / ambient light interrupt asserted. (AINT)
+    pub light_interrupt: bool,
+    /// Proximity interrupt asserted. (PINT)
+    pub proximity_interrupt: bool,
+    /// Proximity or gesture analog saturation occurred. (PGSAT)
+    pub proximity_gesture_saturation: bool,
+    /// Clear photodiode saturation occurred. (CPSAT)
+    pub clear_photodiode_saturation: bool,
+}
+
 /// Color / ambient light data.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct LightData {
@@ -216,6 +585,93 @@ pub struct LightData {
     pub blue: u16,
 }
 
+/// Color / ambient light data with the infrared component removed.
+///
+/// The clear channel includes infrared light, which skews the readings under
+/// e.g. incandescent light. The infrared component is estimated as
+/// `(red + green + blue - clear) / 2` and subtracted from each channel.
+/// The resulting values can be negative.
+///
+/// This can be obtained with [`LightData::ir_compensated()`](struct.LightData.html#method.ir_compensated).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IrCompensatedLightData {
+    /// Clear channel value without infrared.
+    pub clear: i32,
+    /// Red channel value without infrared.
+    pub red: i32,
+    /// Green channel value without infrared.
+    pub green: i32,
+    /// Blue channel value without infrared.
+    pub blue: i32,
+    /// Estimated infrared component.
+    pub ir: u16,
+}
+
+/// Color / ambient light sample taken by the auto-ranging controller.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightSample {
+    /// Color / ambient light data.
+    pub data: LightData,
+    /// Integration time and gain with which the data was measured.
+    pub settings: LightSettings,
+    /// Whether the clear channel was saturated.
+    ///
+    /// In this case the photometric values computed from the data are not accurate.
+    pub saturated: bool,
+}
+
+/// Color and ambient light measurement settings.
+///
+/// These are needed to convert the raw color / ambient light data into photometric values.
+/// The current settings of the driver can be retrieved with
+/// [`light_settings()`](struct.Apds9960.html#method.light_settings).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightSettings {
+    /// Number of integration cycles `[1-256]`. Each cycle takes 2.78ms.
+    pub integration_cycles: u16,
+    /// Color and ambient light gain.
+    pub gain: LightGain,
+}
+
+/// Coefficients for the conversion of color / ambient light data into lux and
+/// correlated color temperature.
+///
+/// The default values correspond to the device without any cover glass
+/// as given in the ams DN40 application note for this family of RGBC sensors.
+/// For a device behind a cover window, the device and glass factor (DGF) should
+/// be determined by comparing the result with a reference lux meter:
+/// `dgf = default_dgf * reference_lux / computed_lux`.
+/// Similarly, the color temperature coefficient and offset can be fitted from
+/// measurements of two light sources with known color temperature.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightCoefficients {
+    /// Red channel coefficient in thousandths. (default: 136)
+    pub red: i32,
+    /// Green channel coefficient in thousandths. (default: 1000)
+    pub green: i32,
+    /// Blue channel coefficient in thousandths. (default: -444)
+    pub blue: i32,
+    /// Device and glass factor (DGF). (default: 310)
+    pub dgf: u32,
+    /// Color temperature coefficient in Kelvin. (default: 3810)
+    pub ct_coef: u32,
+    /// Color temperature offset in Kelvin. (default: 1391)
+    pub ct_offset: u32,
+}
+
+impl Default for LightCoefficients {
+    fn default() -> Self {
+        LightCoefficients {
+            red: 136,
+            green: 1000,
+            blue: -444,
+            dgf: 310,
+            ct_coef: 3810,
+            ct_offset: 1391,
+        }
+    }
+}
+
 const DEV_ADDR: u8 = 0x39;
 
 struct Register;
@@ -227,7 +683,10 @@ impl Register {
     const AIHTL: u8 = 0x86;
     const PILT: u8 = 0x89;
     const PIHT: u8 = 0x8B;
+    const PERS: u8 = 0x8C;
     const CONFIG1: u8 = 0x8D;
+    const PPULSE: u8 = 0x8E;
+    const CONTROL: u8 = 0x8F;
     const CONFIG2: u8 = 0x90;
     const ID: u8 = 0x92;
     const STATUS: u8 = 0x93;
@@ -238,13 +697,17 @@ impl Register {
     const PDATA: u8 = 0x9C;
     const POFFSET_UR: u8 = 0x9D;
     const POFFSET_DL: u8 = 0x9E;
+    const CONFIG3: u8 = 0x9F;
     const GPENTH: u8 = 0xA0;
     const GPEXTH: u8 = 0xA1;
     const GCONFIG1: u8 = 0xA2;
+    const GCONFIG2: u8 = 0xA3;
     const GOFFSET_U: u8 = 0xA4;
     const GOFFSET_D: u8 = 0xA5;
-    const GOFFSET_L: u8 = 0xA6;
-    const GOFFSET_R: u8 = 0xA7;
+    const GPULSE: u8 = 0xA6;
+    const GOFFSET_L: u8 = 0xA7;
+    const GOFFSET_R: u8 = 0xA9;
+    const GCONFIG3: u8 = 0xAA;
     const GCONFIG4: u8 = 0xAB;
     const GFLVL: u8 = 0xAE;
     const GSTATUS: u8 = 0xAF;
@@ -266,6 +729,10 @@ trait BitFlags<T = Self> {
         }
     }
 
+    fn with_bits(&self, mask: u8, value: u8) -> T {
+        Self::create((self.value() & !mask) | (value & mask))
+    }
+
     fn is(&self, mask: u8, value: bool) -> bool {
         ((self.value() & mask) != 0) == value
     }
@@ -280,7 +747,7 @@ mod register {
             impl BitFlags for $name {
                 const ADDRESS: u8 = Register::$reg;
                 fn create(value: u8) -> Self {
-                    Self { 0: value }
+                    Self(value)
                 }
                 fn value(&self) -> u8 {
                     self.0
@@ -303,6 +770,23 @@ mod register {
     }
     impl_bitflags!(Enable, ENABLE);
 
+    #[derive(Debug)]
+    pub struct ATime(u8);
+    impl Default for ATime {
+        fn default() -> Self {
+            Self(0xFF)
+        }
+    }
+    impl_bitflags!(ATime, ATIME);
+
+    #[derive(Debug, Default)]
+    pub struct Pers(u8);
+    impl Pers {
+        pub const PPERS: u8 = 0b1111_0000;
+        pub const APERS: u8 = 0b0000_1111;
+    }
+    impl_bitflags!(Pers, PERS);
+
     #[derive(Debug)]
     pub struct Config1(u8);
     impl Config1 {
@@ -312,37 +796,123 @@ mod register {
 
     impl Default for Config1 {
         fn default() -> Self {
-            Self { 0: 0x40 }
+            Self(0x40)
         }
     }
 
+    #[derive(Debug)]
+    pub struct PPulse(u8);
+    impl PPulse {
+        pub const PPLEN1: u8 = 0b1000_0000;
+        pub const PPLEN0: u8 = 0b0100_0000;
+        pub const PPULSE: u8 = 0b0011_1111;
+    }
+    impl_bitflags!(PPulse, PPULSE);
+
+    impl Default for PPulse {
+        fn default() -> Self {
+            Self(0x40)
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub struct Control(u8);
+    impl Control {
+        pub const LDRIVE1: u8 = 0b1000_0000;
+        pub const LDRIVE0: u8 = 0b0100_0000;
+        pub const PGAIN1: u8 = 0b0000_1000;
+        pub const PGAIN0: u8 = 0b0000_0100;
+        pub const AGAIN1: u8 = 0b0000_0010;
+        pub const AGAIN0: u8 = 0b0000_0001;
+    }
+    impl_bitflags!(Control, CONTROL);
+
     #[derive(Debug)]
     pub struct Config2(u8);
     impl Config2 {
         pub const PSIEN: u8 = 0b1000_0000;
         pub const CPSIEN: u8 = 0b0100_0000;
+        pub const LED_BOOST1: u8 = 0b0010_0000;
+        pub const LED_BOOST0: u8 = 0b0001_0000;
     }
     impl_bitflags!(Config2, CONFIG2);
 
     impl Default for Config2 {
         fn default() -> Self {
-            Self { 0: 1 }
+            Self(1)
         }
     }
 
+    #[derive(Debug, Default)]
+    pub struct Config3(u8);
+    impl Config3 {
+        pub const PCMP: u8 = 0b0010_0000;
+        pub const SAI: u8 = 0b0001_0000;
+        pub const PMASK_U: u8 = 0b0000_1000;
+        pub const PMASK_D: u8 = 0b0000_0100;
+        pub const PMASK_L: u8 = 0b0000_0010;
+        pub const PMASK_R: u8 = 0b0000_0001;
+    }
+    impl_bitflags!(Config3, CONFIG3);
+
     #[derive(Debug, Default)]
     pub struct GConfig1(u8);
     impl GConfig1 {
         pub const GFIFOTH1: u8 = 0b1000_0000;
         pub const GFIFOTH0: u8 = 0b0100_0000;
+        pub const GEXMSK_U: u8 = 0b0010_0000;
+        pub const GEXMSK_D: u8 = 0b0001_0000;
+        pub const GEXMSK_L: u8 = 0b0000_1000;
+        pub const GEXMSK_R: u8 = 0b0000_0100;
+        pub const GEXPERS1: u8 = 0b0000_0010;
+        pub const GEXPERS0: u8 = 0b0000_0001;
     }
     impl_bitflags!(GConfig1, GCONFIG1);
 
+    #[derive(Debug, Default)]
+    pub struct GConfig2(u8);
+    impl GConfig2 {
+        pub const GGAIN1: u8 = 0b0100_0000;
+        pub const GGAIN0: u8 = 0b0010_0000;
+        pub const GLDRIVE1: u8 = 0b0001_0000;
+        pub const GLDRIVE0: u8 = 0b0000_1000;
+        pub const GWTIME: u8 = 0b0000_0111;
+    }
+    impl_bitflags!(GConfig2, GCONFIG2);
+
+    #[derive(Debug)]
+    pub struct GPulse(u8);
+    impl GPulse {
+        pub const GPLEN1: u8 = 0b1000_0000;
+        pub const GPLEN0: u8 = 0b0100_0000;
+        pub const GPULSE: u8 = 0b0011_1111;
+    }
+    impl_bitflags!(GPulse, GPULSE);
+
+    impl Default for GPulse {
+        fn default() -> Self {
+            Self(0x40)
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub struct GConfig3(u8);
+    impl GConfig3 {
+        pub const GDIMS1: u8 = 0b0000_0010;
+        pub const GDIMS0: u8 = 0b0000_0001;
+    }
+    impl_bitflags!(GConfig3, GCONFIG3);
+
     #[derive(Debug, Default)]
     pub struct Status(u8);
     impl Status {
         pub const AVALID: u8 = 0b0000_0001;
         pub const PVALID: u8 = 0b0000_0010;
+        pub const GINT: u8 = 0b0000_0100;
+        pub const AINT: u8 = 0b0001_0000;
+        pub const PINT: u8 = 0b0010_0000;
+        pub const PGSAT: u8 = 0b0100_0000;
+        pub const CPSAT: u8 = 0b1000_0000;
     }
     impl_bitflags!(Status, STATUS);
 
@@ -351,6 +921,7 @@ mod register {
     impl GConfig4 {
         pub const GMODE: u8 = 0b0000_0001;
         pub const GIEN: u8 = 0b0000_0010;
+        pub const GFIFO_CLR: u8 = 0b0000_0100;
     }
     impl_bitflags!(GConfig4, GCONFIG4);
 
@@ -363,42 +934,208 @@ mod register {
     impl_bitflags!(GStatus, GSTATUS);
 }
 
+/// Color / ambient light auto-ranging controller.
+///
+/// This adjusts the color / ambient light gain and integration time of an [`Apds9960`]
+/// driver depending on the light level so that the measurements stay within range.
+/// Each sample is returned together with the settings used to measure it
+/// so that it can be converted into photometric values.
+///
+/// When the clear channel is saturated (or close to it) the sensitivity is reduced and
+/// when the clear channel count is too low the sensitivity is increased. The sensitivity
+/// changes by a factor of 4 on each step, from 64x gain and 64 integration cycles (178ms)
+/// down to 1x gain and 1 integration cycle (2.78ms).
+///
+/// The clear photodiode saturation flag is cleared when set, which also clears any pending
+/// ambient light interrupt. Ambient light interrupts should therefore not be relied upon
+/// while using this controller.
+///
+/// ```no_run
+/// use apds9960::{Apds9960, LightAutoRange, LightCoefficients};
+/// use linux_embedded_hal::I2cdev;
+/// use nb::block;
+///
+/// # fn main() {
+/// let dev = I2cdev::new("/dev/i2c-1").unwrap();
+/// let mut sensor = Apds9960::new(dev);
+/// sensor.enable().unwrap();
+/// sensor.enable_light().unwrap();
+/// let mut auto_range = LightAutoRange::new();
+/// loop {
+///     let sample = block!(auto_range.read_light(&mut sensor)).unwrap();
+///     if !sample.saturated {
+///         let lux = sample
+///             .data
+///             .millilux(&sample.settings, &LightCoefficients::default());
+///         println!("Lux: {:?}", lux.map(|millilux| millilux / 1000));
+///     }
+/// }
+/// # }
+/// ```
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async", self = "LightAutoRangeAsync")
+)]
+#[derive(Debug)]
+pub struct LightAutoRange {
+    step: usize,
+    configured: bool,
+    settling: bool,
+}
+
+/// Gesture recognizer.
+///
+/// This processes the datasets read from the gesture FIFO with
+/// [`read_gesture_dataset()`](struct.Apds9960.html#method.read_gesture_dataset) or
+/// [`read_gesture_data()`](struct.Apds9960.html#method.read_gesture_data) and recognizes
+/// up, down, left and right gestures as well as near, far and hover depth gestures.
+/// It does not allocate and does not access the device.
+///
+/// A gesture starts with the first dataset in which any photodiode value exceeds the noise
+/// threshold and ends with the first dataset in which none does. The ratios between the
+/// up/down and left/right photodiode values are computed for the first (entry) and last (exit)
+/// dataset of the gesture and the gesture direction is given by the change of these ratios
+/// if it exceeds the sensitivity.
+///
+/// Otherwise, the trend of the total photodiode energy (the sum of the four values) over the
+/// gesture is computed. If the energy increases or decreases by more than the depth threshold
+/// over a gesture lasting at least the depth duration, a near or far gesture is recognized.
+/// Proximity readings can be provided as well, in which case a depth gesture is only recognized
+/// if the proximity did not change in the opposite direction.
+/// A hover gesture is recognized as soon as the energy stays within the hover threshold for
+/// the hover duration. No further gesture is recognized until the hovering gesture ends.
+///
+/// The durations are given as a number of datasets. A dataset is measured on every gesture
+/// cycle, which lasts the LED pulses plus the gesture wait time.
+///
+/// ```no_run
+/// use apds9960::{Apds9960, GestureRecognizer};
+/// use linux_embedded_hal::I2cdev;
+///
+/// # fn main() {
+/// let dev = I2cdev::new("/dev/i2c-1").unwrap();
+/// let mut sensor = Apds9960::new(dev);
+/// sensor.enable().unwrap();
+/// sensor.enable_proximity().unwrap();
+/// sensor.set_gesture_proximity_entry_threshold(40).unwrap();
+/// sensor.enable_gesture().unwrap();
+/// let mut recognizer = GestureRecognizer::new();
+/// loop {
+///     let gesture = match sensor.read_gesture_dataset() {
+///         Ok(dataset) => recognizer.process_dataset(dataset),
+///         Err(nb::Error::WouldBlock) if !sensor.is_gesture_mode_enabled().unwrap() => {
+///             recognizer.finish()
+///         }
+///         Err(nb::Error::WouldBlock) => None,
+///         Err(nb::Error::Other(e)) => panic!("{:?}", e),
+///     };
+///     if let Some(gesture) = gesture {
+///         println!("Gesture: {:?}", gesture);
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    orientation: GestureOrientation,
+    noise_threshold: u8,
+    sensitivity: u8,
+    depth_threshold: u8,
+    depth_duration: u16,
+    hover_threshold: u8,
+    hover_duration: u16,
+    entry: Option<GestureDataset>,
+    exit: GestureDataset,
+    datasets: u32,
+    energy_sum: i64,
+    weighted_energy_sum: i64,
+    hover_reference: u16,
+    hover_datasets: u16,
+    hovering: bool,
+    proximity: Option<(u8, u8)>,
+}
+
 /// APDS9960 device driver.
+///
+/// `Apds9960Async` is the `async` variant of this driver (see the `async` feature).
+/// Note that its methods returning `nb::Result`, like `read_proximity()`, do not wait
+/// for the data to be ready. Just like in `Apds9960`, they return `nb::Error::WouldBlock`
+/// if no new data is available.
+#[maybe_async_cfg::maybe(sync(keep_self), async(feature = "async", self = "Apds9960Async"))]
 #[derive(Debug, Default)]
 pub struct Apds9960<I2C> {
     /// The concrete I²C device implementation.
     i2c: I2C,
     enable: register::Enable,
+    atime: register::ATime,
+    pers: register::Pers,
     config1: register::Config1,
+    ppulse: register::PPulse,
+    control: register::Control,
     config2: register::Config2,
+    config3: register::Config3,
     gconfig1: register::GConfig1,
+    gconfig2: register::GConfig2,
+    gpulse: register::GPulse,
+    gconfig3: register::GConfig3,
     gconfig4: register::GConfig4,
+    variant: Option<DeviceVariant>,
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async"),
+    idents(
+        Apds9960(sync, async = "Apds9960Async"),
+        embedded_hal(sync, async = "embedded_hal_async")
+    )
+)]
 impl<I2C, E> Apds9960<I2C>
 where
-    I2C: i2c::Write<Error = E>,
+    I2C: embedded_hal::i2c::I2c<Error = E>,
 {
     /// Create new instance of the APDS9960 device.
     pub fn new(i2c: I2C) -> Self {
         Apds9960 {
             i2c,
             enable: register::Enable::default(),
+            atime: register::ATime::default(),
+            pers: register::Pers::default(),
             config1: register::Config1::default(),
+            ppulse: register::PPulse::default(),
+            control: register::Control::default(),
             config2: register::Config2::default(),
+            config3: register::Config3::default(),
             gconfig1: register::GConfig1::default(),
+            gconfig2: register::GConfig2::default(),
+            gpulse: register::GPulse::default(),
+            gconfig3: register::GConfig3::default(),
             gconfig4: register::GConfig4::default(),
+            variant: None,
         }
     }
 
+    /// Get the device variant identified with [`probe()`](struct.Apds9960.html#method.probe).
+    ///
+    /// Returns `None` if the device has not been probed yet.
+    pub fn device_variant(&self) -> Option<DeviceVariant> {
+        self.variant
+    }
+
     /// Destroy driver instance, return I²C bus instance.
     pub fn destroy(self) -> I2C {
         self.i2c
     }
 }
 
+mod calibration;
+mod cct;
+pub mod color;
 mod config;
 mod gesture;
+mod ir;
 mod light;
+mod light_auto_range;
+mod lux;
 mod proximity;
 mod reading;
@@ -1,22 +1,33 @@
-use hal::blocking::i2c;
-use {
-    register::{Config2, Enable, Status},
-    Apds9960, BitFlags, Error, LightData, Register,
+#[cfg(feature = "async")]
+use crate::Apds9960Async;
+use crate::{
+    config::{cycles_to_duration, duration_to_cycles, CYCLE_US},
+    register::{ATime, Config2, Control, Enable, Pers},
+    Apds9960, BitFlags, Error, LightData, LightGain, LightSettings, Register,
 };
+use core::time::Duration;
 
 /// Color and ambient light.
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async"),
+    idents(
+        Apds9960(sync, async = "Apds9960Async"),
+        embedded_hal(sync, async = "embedded_hal_async")
+    )
+)]
 impl<I2C, E> Apds9960<I2C>
 where
-    I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E>,
+    I2C: embedded_hal::i2c::I2c<Error = E>,
 {
     /// Enable color and ambient light detection.
-    pub fn enable_light(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_enable(Enable::AEN, true)
+    pub async fn enable_light(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::AEN, true).await
     }
 
     /// Disable color and ambient light detection.
-    pub fn disable_light(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_enable(Enable::AEN, false)
+    pub async fn disable_light(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::AEN, false).await
     }
 
     /// Set the color and ambient light integration time.
@@ -24,62 +35,172 @@ where
     /// The value parameter must be a 2's complement of the number of cycles.
     ///
     /// Per default this is set to `0xFF` (1 cycle) and each cycle has a fixed duration of 2.78ms.
-    pub fn set_light_integration_time(&mut self, value: u8) -> Result<(), Error<E>> {
-        self.write_register(Register::ATIME, value)
+    ///
+    /// See also: [`set_light_integration_cycles()`](struct.Apds9960.html#method.set_light_integration_cycles)
+    /// and [`set_light_integration_duration()`](struct.Apds9960.html#method.set_light_integration_duration).
+    pub async fn set_light_integration_time(&mut self, value: u8) -> Result<(), Error<E>> {
+        let new = ATime::create(value);
+        self.config_register(&new).await?;
+        self.atime = new;
+        Ok(())
+    }
+
+    /// Set the color and ambient light integration time as a number of cycles.
+    ///
+    /// Each cycle has a fixed duration of 2.78ms.
+    /// Valid values are in the range `[1-256]`, otherwise `Error::InvalidInputData` is returned.
+    pub async fn set_light_integration_cycles(&mut self, cycles: u16) -> Result<(), Error<E>> {
+        if cycles == 0 || cycles > 256 {
+            return Err(Error::InvalidInputData);
+        }
+        self.set_light_integration_time((256 - cycles) as u8).await
+    }
+
+    /// Set the color and ambient light integration time as a duration.
+    ///
+    /// The duration is rounded to the nearest number of 2.78ms cycles (at least 1)
+    /// and the integration time actually achieved is returned.
+    ///
+    /// Durations longer than 256 cycles (711.68ms) return `Error::InvalidInputData`.
+    pub async fn set_light_integration_duration(
+        &mut self,
+        duration: Duration,
+    ) -> Result<Duration, Error<E>> {
+        let cycles = duration_to_cycles(duration, CYCLE_US).ok_or(Error::InvalidInputData)?;
+        self.set_light_integration_cycles(cycles).await?;
+        Ok(cycles_to_duration(cycles, CYCLE_US))
+    }
+
+    /// Get the maximum color and ambient light channel count for the current integration time.
+    ///
+    /// This is 1025 counts per integration cycle up to a maximum of 65535.
+    /// Data at this level means the channel is saturated.
+    pub fn max_light_count(&self) -> u16 {
+        self.light_settings().max_count()
+    }
+
+    /// Set the color and ambient light integration time and gain.
+    ///
+    /// Returns `Error::InvalidInputData` if the number of integration cycles is not
+    /// in the range `[1-256]`.
+    pub async fn set_light_settings(&mut self, settings: LightSettings) -> Result<(), Error<E>> {
+        self.set_light_integration_cycles(settings.integration_cycles)
+            .await?;
+        self.set_light_gain(settings.gain).await
+    }
+
+    /// Get the current color and ambient light integration time and gain settings.
+    ///
+    /// These can be used to compute the illuminance from the color / ambient light data.
+    /// See [`LightData::millilux()`](struct.LightData.html#method.millilux).
+    pub fn light_settings(&self) -> LightSettings {
+        let gain = match (
+            self.control.is(Control::AGAIN1, true),
+            self.control.is(Control::AGAIN0, true),
+        ) {
+            (false, false) => LightGain::X1,
+            (false, true) => LightGain::X4,
+            (true, false) => LightGain::X16,
+            (true, true) => LightGain::X64,
+        };
+        LightSettings {
+            integration_cycles: 256 - u16::from(self.atime.value()),
+            gain,
+        }
+    }
+
+    /// Set the color and ambient light gain.
+    pub async fn set_light_gain(&mut self, gain: LightGain) -> Result<(), Error<E>> {
+        let flags = match gain {
+            LightGain::X1 => (false, false),
+            LightGain::X4 => (false, true),
+            LightGain::X16 => (true, false),
+            LightGain::X64 => (true, true),
+        };
+        let new = self
+            .control
+            .with(Control::AGAIN1, flags.0)
+            .with(Control::AGAIN0, flags.1);
+        self.config_register(&new).await?;
+        self.control = new;
+        Ok(())
     }
 
     /// Enable ambient light interrupt generation.
-    pub fn enable_light_interrupts(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_enable(Enable::AIEN, true)
+    pub async fn enable_light_interrupts(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::AIEN, true).await
     }
 
     /// Disable ambient light interrupt generation.
-    pub fn disable_light_interrupts(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_enable(Enable::AIEN, false)
+    pub async fn disable_light_interrupts(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::AIEN, false).await
     }
 
     /// Enable clear channel ambient light saturation interrupt generation.
-    pub fn enable_light_saturation_interrupts(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_config2(Config2::CPSIEN, true)
+    pub async fn enable_light_saturation_interrupts(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config2(Config2::CPSIEN, true).await
     }
 
     /// Disable clear channel ambient light saturation interrupt generation.
-    pub fn disable_light_saturation_interrupts(&mut self) -> Result<(), Error<E>> {
-        self.set_flag_config2(Config2::CPSIEN, false)
+    pub async fn disable_light_saturation_interrupts(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config2(Config2::CPSIEN, false).await
     }
 
     /// Set the clear channel ambient light interrupt low threshold.
     ///
     /// An interrupt will be generated if light interrupts are enabled and the clear data is less
     /// than this value.
-    pub fn set_light_low_threshold(&mut self, threshold: u16) -> Result<(), Error<E>> {
-        self.write_double_register(Register::AILTL, threshold)
+    pub async fn set_light_low_threshold(&mut self, threshold: u16) -> Result<(), Error<E>> {
+        self.write_double_register(Register::AILTL, threshold).await
     }
 
     /// Set the clear channel ambient light interrupt high threshold.
     ///
     /// An interrupt will be generated if light interrupts are enabled and the clear data is greater
     /// than this value.
-    pub fn set_light_high_threshold(&mut self, threshold: u16) -> Result<(), Error<E>> {
-        self.write_double_register(Register::AIHTL, threshold)
+    pub async fn set_light_high_threshold(&mut self, threshold: u16) -> Result<(), Error<E>> {
+        self.write_double_register(Register::AIHTL, threshold).await
+    }
+
+    /// Set the clear channel ambient light interrupt persistence.
+    ///
+    /// This is the number of consecutive color / ambient light cycles out of the threshold
+    /// range required to generate an interrupt.
+    /// Valid values are `0`, `1`, `2`, `3` and multiples of 5 from `5` to `60`.
+    /// With `0` (default) an interrupt is generated after every color / ambient light cycle.
+    ///
+    /// Returns `Error::InvalidInputData` if the number of cycles is not valid.
+    pub async fn set_light_interrupt_persistence(&mut self, cycles: u8) -> Result<(), Error<E>> {
+        let apers = match cycles {
+            0..=3 => cycles,
+            5 | 10 | 15 | 20 | 25 | 30 | 35 | 40 | 45 | 50 | 55 | 60 => cycles / 5 + 3,
+            _ => return Err(Error::InvalidInputData),
+        };
+        let new = self.pers.with_bits(Pers::APERS, apers);
+        self.config_register(&new).await?;
+        self.pers = new;
+        Ok(())
     }
 
     /// Clear ambient light interrupt.
-    pub fn clear_light_interrupt(&mut self) -> Result<(), Error<E>> {
-        self.touch_register(Register::CICLEAR)
+    pub async fn clear_light_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.touch_register(Register::CICLEAR).await
     }
 
     /// Read the color / ambient light sensor data.
     ///
     /// Returns `nb::Error::WouldBlock` as long as the data is not ready.
     /// This clears the data ready flag.
-    pub fn read_light(&mut self) -> nb::Result<LightData, Error<E>> {
-        if !self.is_light_data_valid().map_err(nb::Error::Other)? {
+    pub async fn read_light(&mut self) -> nb::Result<LightData, Error<E>> {
+        if !self.is_light_data_valid().await.map_err(nb::Error::Other)? {
             return Err(nb::Error::WouldBlock);
         }
+        self.read_light_data().await.map_err(nb::Error::Other)
+    }
+
+    pub(crate) async fn read_light_data(&mut self) -> Result<LightData, Error<E>> {
         let mut data = [0; 8];
-        self.read_data(Register::CDATAL, &mut data)
-            .map_err(nb::Error::Other)?;
+        self.read_data(Register::CDATAL, &mut data).await?;
         Ok(LightData {
             clear: (u16::from(data[1]) << 8) | u16::from(data[0]),
             red: (u16::from(data[3]) << 8) | u16::from(data[2]),
@@ -92,49 +213,49 @@ where
     ///
     /// Returns `nb::Error::WouldBlock` as long as the data is not ready.
     /// This clears the data ready flag.
-    pub fn read_light_clear(&mut self) -> nb::Result<u16, Error<E>> {
-        self.read_light_channel(Register::CDATAL)
+    pub async fn read_light_clear(&mut self) -> nb::Result<u16, Error<E>> {
+        self.read_light_channel(Register::CDATAL).await
     }
 
     /// Read the color / ambient light sensor red channel data.
     ///
     /// Returns `nb::Error::WouldBlock` as long as the data is not ready.
     /// This clears the data ready flag.
-    pub fn read_light_red(&mut self) -> nb::Result<u16, Error<E>> {
-        self.read_light_channel(Register::RDATAL)
+    pub async fn read_light_red(&mut self) -> nb::Result<u16, Error<E>> {
+        self.read_light_channel(Register::RDATAL).await
     }
 
     /// Read the color / ambient light sensor green channel data.
     ///
     /// Returns `nb::Error::WouldBlock` as long as the data is not ready.
     /// This clears the data ready flag.
-    pub fn read_light_green(&mut self) -> nb::Result<u16, Error<E>> {
-        self.read_light_channel(Register::GDATAL)
+    pub async fn read_light_green(&mut self) -> nb::Result<u16, Error<E>> {
+        self.read_light_channel(Register::GDATAL).await
     }
 
     /// Read the color / ambient light sensor blue channel data.
     ///
     /// Returns `nb::Error::WouldBlock` as long as the data is not ready.
     /// This clears the data ready flag.
-    pub fn read_light_blue(&mut self) -> nb::Result<u16, Error<E>> {
-        self.read_light_channel(Register::BDATAL)
+    pub async fn read_light_blue(&mut self) -> nb::Result<u16, Error<E>> {
+        self.read_light_channel(Register::BDATAL).await
     }
 
     /// Read whether the color and ambient light sensor data is valid.
     ///
     /// This is checked internally in the `read_light_*()` methods as well.
     #[allow(clippy::wrong_self_convention)]
-    pub fn is_light_data_valid(&mut self) -> Result<bool, Error<E>> {
-        let status = self.read_register(Register::STATUS)?;
-        Ok(Status::create(status).is(Status::AVALID, true))
+    pub async fn is_light_data_valid(&mut self) -> Result<bool, Error<E>> {
+        Ok(self.read_status().await?.light_data_valid)
     }
 
-    fn read_light_channel(&mut self, register: u8) -> nb::Result<u16, Error<E>> {
-        if !self.is_light_data_valid().map_err(nb::Error::Other)? {
+    async fn read_light_channel(&mut self, register: u8) -> nb::Result<u16, Error<E>> {
+        if !self.is_light_data_valid().await.map_err(nb::Error::Other)? {
             return Err(nb::Error::WouldBlock);
         }
         let mut data = [0; 2];
         self.read_data(register, &mut data)
+            .await
             .map_err(nb::Error::Other)?;
         Ok((u16::from(data[1]) << 8) | u16::from(data[0]))
     }
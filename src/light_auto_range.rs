@@ -0,0 +1,129 @@
+use crate::{Apds9960, Error, LightAutoRange, LightGain, LightSample, LightSettings};
+#[cfg(feature = "async")]
+use crate::{Apds9960Async, LightAutoRangeAsync};
+
+/// Settings from the most to the least sensitive. Each step is 4 times less sensitive.
+const STEPS: [LightSettings; 7] = [
+    settings(64, LightGain::X64),
+    settings(64, LightGain::X16),
+    settings(64, LightGain::X4),
+    settings(64, LightGain::X1),
+    settings(16, LightGain::X1),
+    settings(4, LightGain::X1),
+    settings(1, LightGain::X1),
+];
+
+/// Initial step. A moderate sensitivity for indoor light levels.
+const INITIAL_STEP: usize = 2;
+
+const fn settings(integration_cycles: u16, gain: LightGain) -> LightSettings {
+    LightSettings {
+        integration_cycles,
+        gain,
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async"),
+    idents(
+        Apds9960(sync, async = "Apds9960Async"),
+        LightAutoRange(sync, async = "LightAutoRangeAsync"),
+        embedded_hal(sync, async = "embedded_hal_async")
+    )
+)]
+impl LightAutoRange {
+    /// Create a new auto-ranging controller.
+    ///
+    /// The settings of the driver will be changed on the first reading.
+    pub fn new() -> Self {
+        LightAutoRange {
+            step: INITIAL_STEP,
+            configured: false,
+            settling: false,
+        }
+    }
+
+    /// Get the current color and ambient light integration time and gain settings.
+    pub fn settings(&self) -> LightSettings {
+        STEPS[self.step]
+    }
+
+    /// Read the color / ambient light data and adjust the settings for the next measurement
+    /// if necessary.
+    ///
+    /// Color and ambient light detection must be enabled.
+    ///
+    /// When the clear photodiode is saturated, the saturation flag is cleared with
+    /// [`clear_light_interrupt()`](struct.Apds9960.html#method.clear_light_interrupt).
+    /// Note that this clears a pending ambient light interrupt as well, so if ambient light
+    /// interrupts are enabled, they may be lost.
+    ///
+    /// Returns `nb::Error::WouldBlock` as long as no data measured with the current settings
+    /// is available. After a settings change, the first available data is discarded
+    /// since it may have been integrated partially with the previous settings.
+    pub async fn read_light<I2C, E>(
+        &mut self,
+        sensor: &mut Apds9960<I2C>,
+    ) -> nb::Result<LightSample, Error<E>>
+    where
+        I2C: embedded_hal::i2c::I2c<Error = E>,
+    {
+        if !self.configured {
+            self.apply(sensor).await.map_err(nb::Error::Other)?;
+            self.configured = true;
+            return Err(nb::Error::WouldBlock);
+        }
+        let status = sensor.read_status().await.map_err(nb::Error::Other)?;
+        if !status.light_data_valid {
+            return Err(nb::Error::WouldBlock);
+        }
+        let data = sensor.read_light_data().await.map_err(nb::Error::Other)?;
+        if status.clear_photodiode_saturation {
+            sensor
+                .clear_light_interrupt()
+                .await
+                .map_err(nb::Error::Other)?;
+        }
+        if self.settling {
+            self.settling = false;
+            return Err(nb::Error::WouldBlock);
+        }
+        let settings = self.settings();
+        let max_count = u32::from(settings.max_count());
+        let clear = u32::from(data.clear);
+        let saturated = status.clear_photodiode_saturation || clear >= max_count;
+        if (saturated || clear >= max_count * 9 / 10) && self.step < STEPS.len() - 1 {
+            self.step += 1;
+            self.apply(sensor).await.map_err(nb::Error::Other)?;
+        } else if clear < max_count / 8 && self.step > 0 {
+            self.step -= 1;
+            self.apply(sensor).await.map_err(nb::Error::Other)?;
+        }
+        Ok(LightSample {
+            data,
+            settings,
+            saturated,
+        })
+    }
+
+    async fn apply<I2C, E>(&mut self, sensor: &mut Apds9960<I2C>) -> Result<(), Error<E>>
+    where
+        I2C: embedded_hal::i2c::I2c<Error = E>,
+    {
+        sensor.set_light_settings(self.settings()).await?;
+        self.settling = true;
+        Ok(())
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async"),
+    idents(LightAutoRange(sync, async = "LightAutoRangeAsync"))
+)]
+impl Default for LightAutoRange {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    config::CYCLE_US, IrCompensatedLightData, LightCoefficients, LightData, LightGain,
+    LightSettings,
+};
+
+impl LightSettings {
+    /// Get the maximum channel count for these settings.
+    ///
+    /// This is 1025 counts per integration cycle up to a maximum of 65535.
+    /// Data at this level means the channel is saturated.
+    pub fn max_count(&self) -> u16 {
+        core::cmp::min(1025 * u32::from(self.integration_cycles), 0xFFFF) as u16
+    }
+
+    pub(crate) fn gain_factor(&self) -> i64 {
+        match self.gain {
+            LightGain::X1 => 1,
+            LightGain::X4 => 4,
+            LightGain::X16 => 16,
+            LightGain::X64 => 64,
+        }
+    }
+}
+
+impl LightData {
+    /// Compute the illuminance in millilux.
+    ///
+    /// The infrared component is estimated and removed from the red, green and blue
+    /// channels, which are then weighted with the given coefficients and scaled
+    /// according to the integration time and gain used for the measurement.
+    /// See [`IrCompensatedLightData::millilux()`](struct.IrCompensatedLightData.html#method.millilux).
+    ///
+    /// Returns `None` if the clear channel is saturated, as the result would not be accurate.
+    /// In this case the integration time or gain should be reduced.
+    ///
+    /// Only integer arithmetic is used.
+    pub fn millilux(
+        &self,
+        settings: &LightSettings,
+        coefficients: &LightCoefficients,
+    ) -> Option<u32> {
+        self.ir_compensated().millilux(settings, coefficients)
+    }
+}
+
+impl IrCompensatedLightData {
+    /// Compute the illuminance in millilux.
+    ///
+    /// The red, green and blue channels are weighted with the given coefficients and scaled
+    /// according to the integration time and gain used for the measurement.
+    ///
+    /// Returns `None` if the clear channel is saturated, as the result would not be accurate.
+    /// In this case the integration time or gain should be reduced.
+    ///
+    /// Only integer arithmetic is used.
+    pub fn millilux(
+        &self,
+        settings: &LightSettings,
+        coefficients: &LightCoefficients,
+    ) -> Option<u32> {
+        if self.is_saturated(settings.max_count()) {
+            return None;
+        }
+        // in thousandths of a count
+        let weighted = i64::from(coefficients.red) * i64::from(self.red)
+            + i64::from(coefficients.green) * i64::from(self.green)
+            + i64::from(coefficients.blue) * i64::from(self.blue);
+        // counts per lux = integration time [ms] * gain / DGF
+        let divisor =
+            i64::from(settings.integration_cycles) * i64::from(CYCLE_US) * settings.gain_factor();
+        // The product does not fit in an i64 for large device and glass factors.
+        let millilux =
+            i128::from(weighted) * i128::from(coefficients.dgf) * 1000 / i128::from(divisor);
+        Some(core::cmp::min(core::cmp::max(millilux, 0), i128::from(u32::MAX)) as u32)
+    }
+}
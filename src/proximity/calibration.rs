@@ -0,0 +1,132 @@
+#[cfg(feature = "async")]
+use crate::Apds9960Async;
+use crate::{
+    calibration::{Baseline, OffsetSearch, MAX_POLLS},
+    config::register_to_offset,
+    register::{Config3, Enable},
+    Apds9960, BitFlags, Error, ProximityCalibration, Register,
+};
+
+/// Proximity photodiode masks and gain compensation.
+const MASKS: u8 =
+    Config3::PMASK_U | Config3::PMASK_D | Config3::PMASK_L | Config3::PMASK_R | Config3::PCMP;
+
+/// Proximity crosstalk calibration.
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async"),
+    idents(
+        Apds9960(sync, async = "Apds9960Async"),
+        embedded_hal(sync, async = "embedded_hal_async")
+    )
+)]
+impl<I2C, E> Apds9960<I2C>
+where
+    I2C: embedded_hal::i2c::I2c<Error = E>,
+{
+    /// Calibrate the proximity photodiode offsets to compensate the crosstalk.
+    ///
+    /// This must be run with no target in front of the sensor, in the final enclosure.
+    ///
+    /// The up/right and the down/left photodiode pairs are isolated in turn with the
+    /// proximity photodiode masks and gain compensation, and the smallest offset for which
+    /// the average proximity reading is close to zero is searched. Only the device power and
+    /// proximity detection are enabled during the calibration.
+    /// Afterwards, the previous masks and enabled functions are restored and the offsets found
+    /// are set. These are returned so that they can be stored and set again later with
+    /// [`set_proximity_calibration()`](struct.Apds9960.html#method.set_proximity_calibration).
+    ///
+    /// Note that this blocks until enough proximity readings have been taken.
+    /// If the device does not provide new proximity data, `Error::Timeout` is returned.
+    /// On error, the previous offsets are restored as well.
+    pub async fn calibrate_proximity_offsets(&mut self) -> Result<ProximityCalibration, Error<E>> {
+        let enable = self.enable.value();
+        let config3 = self.config3.value();
+        let mut offsets = [0; 2];
+        self.read_data(Register::POFFSET_UR, &mut offsets).await?;
+
+        let result = self.search_proximity_offsets().await;
+        let restored = self.restore_proximity_state(enable, config3).await;
+        let calibration = match result {
+            Ok(calibration) => calibration,
+            Err(e) => {
+                // The error of the calibration is more relevant.
+                let [up_right, down_left] = offsets.map(register_to_offset);
+                let _ = self.set_proximity_offsets(up_right, down_left).await;
+                return Err(e);
+            }
+        };
+        restored?;
+        self.set_proximity_calibration(calibration).await?;
+        Ok(calibration)
+    }
+
+    /// Set the proximity photodiode offsets from a previous calibration.
+    ///
+    /// See [`calibrate_proximity_offsets()`](struct.Apds9960.html#method.calibrate_proximity_offsets).
+    pub async fn set_proximity_calibration(
+        &mut self,
+        calibration: ProximityCalibration,
+    ) -> Result<(), Error<E>> {
+        self.set_proximity_offsets(calibration.up_right_offset, calibration.down_left_offset)
+            .await
+    }
+
+    async fn search_proximity_offsets(&mut self) -> Result<ProximityCalibration, Error<E>> {
+        let new = Enable::create(Enable::PON | Enable::PEN);
+        self.config_register(&new).await?;
+        self.enable = new;
+
+        // The gain compensation must be enabled when two photodiodes are masked.
+        self.set_proximity_masks(Config3::PMASK_D | Config3::PMASK_L | Config3::PCMP)
+            .await?;
+        let up_right_offset = self.search_proximity_offset(Register::POFFSET_UR).await?;
+        self.set_proximity_masks(Config3::PMASK_U | Config3::PMASK_R | Config3::PCMP)
+            .await?;
+        let down_left_offset = self.search_proximity_offset(Register::POFFSET_DL).await?;
+        Ok(ProximityCalibration {
+            up_right_offset,
+            down_left_offset,
+        })
+    }
+
+    /// Restore the masks and enabled functions. Both are attempted even if one fails.
+    async fn restore_proximity_state(&mut self, enable: u8, config3: u8) -> Result<(), Error<E>> {
+        let masks = self.set_proximity_masks(config3).await;
+        let new = Enable::create(enable);
+        self.config_register(&new).await?;
+        self.enable = new;
+        masks
+    }
+
+    async fn set_proximity_masks(&mut self, value: u8) -> Result<(), Error<E>> {
+        let new = self.config3.with_bits(MASKS, value);
+        self.config_register(&new).await?;
+        self.config3 = new;
+        Ok(())
+    }
+
+    async fn search_proximity_offset(&mut self, register: u8) -> Result<i8, Error<E>> {
+        let mut search = OffsetSearch::new();
+        while !search.is_done() {
+            self.write_register(register, search.offset() as u8).await?;
+            let mut baseline = Baseline::new();
+            while !baseline.is_complete() {
+                baseline.add([self.read_proximity_blocking().await?]);
+            }
+            search.update(baseline.average()[0]);
+        }
+        Ok(search.offset())
+    }
+
+    async fn read_proximity_blocking(&mut self) -> Result<u8, Error<E>> {
+        for _ in 0..MAX_POLLS {
+            match self.read_proximity().await {
+                Ok(value) => return Ok(value),
+                Err(nb::Error::WouldBlock) => (),
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+        }
+        Err(Error::Timeout)
+    }
+}
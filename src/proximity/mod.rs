@@ -0,0 +1,297 @@
+mod calibration;
+
+#[cfg(feature = "async")]
+use crate::Apds9960Async;
+use crate::{
+    config::offset_to_register,
+    register::{Config2, Config3, Control, Enable, PPulse, Pers},
+    Apds9960, BitFlags, Error, LedBoost, LedDrive, ProximityGain, ProximityPulseLength, Register,
+    DEV_ADDR,
+};
+
+/// Proximity.
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async"),
+    idents(
+        Apds9960(sync, async = "Apds9960Async"),
+        embedded_hal(sync, async = "embedded_hal_async")
+    )
+)]
+impl<I2C, E> Apds9960<I2C>
+where
+    I2C: embedded_hal::i2c::I2c<Error = E>,
+{
+    /// Enable proximity detection
+    pub async fn enable_proximity(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::PEN, true).await
+    }
+
+    /// Disable proximity detection
+    pub async fn disable_proximity(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::PEN, false).await
+    }
+
+    /// Enable proximity interrupt generation
+    pub async fn enable_proximity_interrupts(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::PIEN, true).await
+    }
+
+    /// Disable proximity interrupt generation
+    pub async fn disable_proximity_interrupts(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_enable(Enable::PIEN, false).await
+    }
+
+    /// Enable proximity saturation interrupt generation
+    pub async fn enable_proximity_saturation_interrupts(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config2(Config2::PSIEN, true).await
+    }
+
+    /// Disable proximity saturation interrupt generation
+    pub async fn disable_proximity_saturation_interrupts(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config2(Config2::PSIEN, false).await
+    }
+
+    /// Set the LED drive strength.
+    ///
+    /// This is used for proximity and gesture detection.
+    pub async fn set_led_drive(&mut self, drive: LedDrive) -> Result<(), Error<E>> {
+        let flags = match drive {
+            LedDrive::Ma100 => (false, false),
+            LedDrive::Ma50 => (false, true),
+            LedDrive::Ma25 => (true, false),
+            LedDrive::Ma12_5 => (true, true),
+        };
+        let new = self
+            .control
+            .with(Control::LDRIVE1, flags.0)
+            .with(Control::LDRIVE0, flags.1);
+        self.config_register(&new).await?;
+        self.control = new;
+        Ok(())
+    }
+
+    /// Set the LED boost.
+    ///
+    /// This is used for proximity and gesture detection.
+    pub async fn set_led_boost(&mut self, boost: LedBoost) -> Result<(), Error<E>> {
+        let flags = match boost {
+            LedBoost::Percent100 => (false, false),
+            LedBoost::Percent150 => (false, true),
+            LedBoost::Percent200 => (true, false),
+            LedBoost::Percent300 => (true, true),
+        };
+        let new = self
+            .config2
+            .with(Config2::LED_BOOST1, flags.0)
+            .with(Config2::LED_BOOST0, flags.1);
+        self.config_register(&new).await?;
+        self.config2 = new;
+        Ok(())
+    }
+
+    /// Set the proximity gain.
+    pub async fn set_proximity_gain(&mut self, gain: ProximityGain) -> Result<(), Error<E>> {
+        let flags = match gain {
+            ProximityGain::X1 => (false, false),
+            ProximityGain::X2 => (false, true),
+            ProximityGain::X4 => (true, false),
+            ProximityGain::X8 => (true, true),
+        };
+        let new = self
+            .control
+            .with(Control::PGAIN1, flags.0)
+            .with(Control::PGAIN0, flags.1);
+        self.config_register(&new).await?;
+        self.control = new;
+        Ok(())
+    }
+
+    /// Set the proximity LED pulse length.
+    pub async fn set_proximity_pulse_length(
+        &mut self,
+        length: ProximityPulseLength,
+    ) -> Result<(), Error<E>> {
+        let flags = match length {
+            ProximityPulseLength::Us4 => (false, false),
+            ProximityPulseLength::Us8 => (false, true),
+            ProximityPulseLength::Us16 => (true, false),
+            ProximityPulseLength::Us32 => (true, true),
+        };
+        let new = self
+            .ppulse
+            .with(PPulse::PPLEN1, flags.0)
+            .with(PPulse::PPLEN0, flags.1);
+        self.config_register(&new).await?;
+        self.ppulse = new;
+        Ok(())
+    }
+
+    /// Set the number of proximity LED pulses per proximity cycle.
+    ///
+    /// Valid values are `1` to `64`. Per default this is set to `1`.
+    ///
+    /// Returns `Error::InvalidInputData` if the count is out of range.
+    pub async fn set_proximity_pulse_count(&mut self, count: u8) -> Result<(), Error<E>> {
+        if count == 0 || count > 64 {
+            return Err(Error::InvalidInputData);
+        }
+        let new = self.ppulse.with_bits(PPulse::PPULSE, count - 1);
+        self.config_register(&new).await?;
+        self.ppulse = new;
+        Ok(())
+    }
+
+    /// Set the proximity interrupt low threshold.
+    pub async fn set_proximity_low_threshold(&mut self, threshold: u8) -> Result<(), Error<E>> {
+        self.write_register(Register::PILT, threshold).await
+    }
+
+    /// Set the proximity interrupt high threshold.
+    pub async fn set_proximity_high_threshold(&mut self, threshold: u8) -> Result<(), Error<E>> {
+        self.write_register(Register::PIHT, threshold).await
+    }
+
+    /// Set the proximity interrupt persistence.
+    ///
+    /// This is the number of consecutive proximity cycles out of the threshold range
+    /// required to generate an interrupt. Valid values are `0` to `15`.
+    /// With `0` (default) an interrupt is generated after every proximity cycle.
+    ///
+    /// Returns `Error::InvalidInputData` if the number of cycles is out of range.
+    pub async fn set_proximity_interrupt_persistence(
+        &mut self,
+        cycles: u8,
+    ) -> Result<(), Error<E>> {
+        if cycles > 15 {
+            return Err(Error::InvalidInputData);
+        }
+        let new = self.pers.with_bits(Pers::PPERS, cycles << 4);
+        self.config_register(&new).await?;
+        self.pers = new;
+        Ok(())
+    }
+
+    /// Enable proximity gain compensation.
+    ///
+    /// This compensates the reduced proximity signal when photodiodes are masked.
+    /// It should be enabled when only one photodiode of a pair (up/down or left/right)
+    /// is masked.
+    pub async fn enable_proximity_gain_compensation(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::PCMP, true).await
+    }
+
+    /// Disable proximity gain compensation.
+    pub async fn disable_proximity_gain_compensation(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::PCMP, false).await
+    }
+
+    /// Enable the proximity up photodiode mask.
+    ///
+    /// The up photodiode will not be used for proximity detection.
+    pub async fn enable_proximity_up_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::PMASK_U, true).await
+    }
+
+    /// Disable the proximity up photodiode mask.
+    pub async fn disable_proximity_up_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::PMASK_U, false).await
+    }
+
+    /// Enable the proximity down photodiode mask.
+    ///
+    /// The down photodiode will not be used for proximity detection.
+    pub async fn enable_proximity_down_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::PMASK_D, true).await
+    }
+
+    /// Disable the proximity down photodiode mask.
+    pub async fn disable_proximity_down_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::PMASK_D, false).await
+    }
+
+    /// Enable the proximity left photodiode mask.
+    ///
+    /// The left photodiode will not be used for proximity detection.
+    pub async fn enable_proximity_left_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::PMASK_L, true).await
+    }
+
+    /// Disable the proximity left photodiode mask.
+    pub async fn disable_proximity_left_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::PMASK_L, false).await
+    }
+
+    /// Enable the proximity right photodiode mask.
+    ///
+    /// The right photodiode will not be used for proximity detection.
+    pub async fn enable_proximity_right_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::PMASK_R, true).await
+    }
+
+    /// Disable the proximity right photodiode mask.
+    pub async fn disable_proximity_right_mask(&mut self) -> Result<(), Error<E>> {
+        self.set_flag_config3(Config3::PMASK_R, false).await
+    }
+
+    /// Set the proximity up/right photodiode offset.
+    ///
+    /// Valid values are in the range `[-127, 127]`, otherwise `Error::InvalidInputData` is returned.
+    pub async fn set_proximity_up_right_offset(&mut self, offset: i8) -> Result<(), Error<E>> {
+        let value = offset_to_register(offset).ok_or(Error::InvalidInputData)?;
+        self.write_register(Register::POFFSET_UR, value).await
+    }
+
+    /// Set the proximity down/left photodiode offset.
+    ///
+    /// Valid values are in the range `[-127, 127]`, otherwise `Error::InvalidInputData` is returned.
+    pub async fn set_proximity_down_left_offset(&mut self, offset: i8) -> Result<(), Error<E>> {
+        let value = offset_to_register(offset).ok_or(Error::InvalidInputData)?;
+        self.write_register(Register::POFFSET_DL, value).await
+    }
+
+    /// Set the proximity up/right and down/left photodiode offset.
+    ///
+    /// Valid values are in the range `[-127, 127]`, otherwise `Error::InvalidInputData` is returned.
+    pub async fn set_proximity_offsets(
+        &mut self,
+        offset_up_right: i8,
+        offset_down_left: i8,
+    ) -> Result<(), Error<E>> {
+        let up_right = offset_to_register(offset_up_right).ok_or(Error::InvalidInputData)?;
+        let down_left = offset_to_register(offset_down_left).ok_or(Error::InvalidInputData)?;
+        self.i2c
+            .write(DEV_ADDR, &[Register::POFFSET_UR, up_right, down_left])
+            .await
+            .map_err(Error::I2C)
+    }
+
+    /// Clear proximity interrupt.
+    pub async fn clear_proximity_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.touch_register(Register::PICLEAR).await
+    }
+
+    /// Read the proximity sensor data.
+    ///
+    /// Returns `nb::Error::WouldBlock` as long as the data is not ready.
+    pub async fn read_proximity(&mut self) -> nb::Result<u8, Error<E>> {
+        if !self
+            .is_proximity_data_valid()
+            .await
+            .map_err(nb::Error::Other)?
+        {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.read_register(Register::PDATA)
+            .await
+            .map_err(nb::Error::Other)
+    }
+
+    /// Read whether the proximity sensor data is valid.
+    ///
+    /// This is checked internally in `read_proximity()` as well.
+    #[allow(clippy::wrong_self_convention)]
+    pub async fn is_proximity_data_valid(&mut self) -> Result<bool, Error<E>> {
+        Ok(self.read_status().await?.proximity_data_valid)
+    }
+}
@@ -1,26 +1,115 @@
-use hal::blocking::i2c;
-use {Apds9960, Error, Register, DEV_ADDR};
+#[cfg(feature = "async")]
+use crate::Apds9960Async;
+use crate::{
+    register::{
+        ATime, Config1, Config2, Config3, Control, Enable, GConfig1, GConfig2, GConfig3, GConfig4,
+        GPulse, PPulse, Pers, Status,
+    },
+    Apds9960, BitFlags, DeviceStatus, DeviceVariant, Error, Register, DEV_ADDR,
+};
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(feature = "async"),
+    idents(
+        Apds9960(sync, async = "Apds9960Async"),
+        embedded_hal(sync, async = "embedded_hal_async")
+    )
+)]
 impl<I2C, E> Apds9960<I2C>
 where
-    I2C: i2c::WriteRead<Error = E>,
+    I2C: embedded_hal::i2c::I2c<Error = E>,
 {
     /// Read the device ID.
     ///
     /// This is per default `0xAB`.
-    pub fn read_device_id(&mut self) -> Result<u8, Error<E>> {
-        self.read_register(Register::ID)
+    pub async fn read_device_id(&mut self) -> Result<u8, Error<E>> {
+        self.read_register(Register::ID).await
     }
 
-    pub(crate) fn read_register(&mut self, register: u8) -> Result<u8, Error<E>> {
+    /// Read the device ID and identify the device variant.
+    ///
+    /// The identified variant is kept and can be retrieved later with
+    /// [`device_variant()`](struct.Apds9960.html#method.device_variant).
+    ///
+    /// Returns `Error::WrongDeviceId` if the device ID does not belong to a known variant.
+    pub async fn probe(&mut self) -> Result<DeviceVariant, Error<E>> {
+        let variant = match self.read_device_id().await? {
+            0xAB => DeviceVariant::Genuine,
+            0xA8 => DeviceVariant::CloneA8,
+            0x9C => DeviceVariant::Clone9C,
+            id => return Err(Error::WrongDeviceId(id)),
+        };
+        self.variant = Some(variant);
+        Ok(variant)
+    }
+
+    /// Read the device status.
+    ///
+    /// All status flags are read in a single I²C transaction.
+    pub async fn read_status(&mut self) -> Result<DeviceStatus, Error<E>> {
+        let status = Status::create(self.read_register(Register::STATUS).await?);
+        Ok(DeviceStatus {
+            light_data_valid: status.is(Status::AVALID, true),
+            proximity_data_valid: status.is(Status::PVALID, true),
+            gesture_interrupt: status.is(Status::GINT, true),
+            light_interrupt: status.is(Status::AINT, true),
+            proximity_interrupt: status.is(Status::PINT, true),
+            proximity_gesture_saturation: status.is(Status::PGSAT, true),
+            clear_photodiode_saturation: status.is(Status::CPSAT, true),
+        })
+    }
+
+    /// Read the configuration back from the device and update the cached register state.
+    ///
+    /// The driver keeps a copy of the configuration registers and assumes the device
+    /// is at its power-on defaults when it is created. If the device may have been
+    /// configured before (e.g. after a reset of the microcontroller but not of the
+    /// device), call this method before changing any settings so that those are kept.
+    pub async fn sync_from_device(&mut self) -> Result<(), Error<E>> {
+        // ENABLE, ATIME
+        let mut enable = [0; 2];
+        self.read_data(Register::ENABLE, &mut enable).await?;
+        // PERS, CONFIG1, PPULSE, CONTROL, CONFIG2
+        let mut config = [0; 5];
+        self.read_data(Register::PERS, &mut config).await?;
+        let config3 = self.read_register(Register::CONFIG3).await?;
+        let mut gconfig12 = [0; 2];
+        self.read_data(Register::GCONFIG1, &mut gconfig12).await?;
+        let gpulse = self.read_register(Register::GPULSE).await?;
+        let mut gconfig34 = [0; 2];
+        self.read_data(Register::GCONFIG3, &mut gconfig34).await?;
+
+        self.enable = Enable::create(enable[0]);
+        self.atime = ATime::create(enable[1]);
+        self.pers = Pers::create(config[0]);
+        self.config1 = Config1::create(config[1]);
+        self.ppulse = PPulse::create(config[2]);
+        self.control = Control::create(config[3]);
+        self.config2 = Config2::create(config[4]);
+        self.config3 = Config3::create(config3);
+        self.gconfig1 = GConfig1::create(gconfig12[0]);
+        self.gconfig2 = GConfig2::create(gconfig12[1]);
+        self.gpulse = GPulse::create(gpulse);
+        self.gconfig3 = GConfig3::create(gconfig34[0]);
+        self.gconfig4 = GConfig4::create(gconfig34[1]).with(GConfig4::GFIFO_CLR, false);
+        Ok(())
+    }
+
+    pub(crate) async fn read_register(&mut self, register: u8) -> Result<u8, Error<E>> {
         let mut data = [0];
-        self.read_data(register, &mut data)?;
+        self.read_data(register, &mut data).await?;
         Ok(data[0])
     }
 
-    pub(crate) fn read_data(&mut self, register: u8, data: &mut [u8]) -> Result<(), Error<E>> {
+    pub(crate) async fn read_data(
+        &mut self,
+        register: u8,
+        data: &mut [u8],
+    ) -> Result<(), Error<E>> {
         self.i2c
             .write_read(DEV_ADDR, &[register], data)
+            .await
             .map_err(Error::I2C)
     }
 }
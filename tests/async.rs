@@ -0,0 +1,155 @@
+#![cfg(feature = "async")]
+use apds9960::{Apds9960Async, LightAutoRangeAsync, LightData, LightGain, LightSettings};
+use embassy_futures::block_on;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTrans};
+mod common;
+use common::{BitFlags, Register, DEFAULT_CONFIG2, DEV_ADDR};
+
+fn new(transactions: &[I2cTrans]) -> Apds9960Async<I2cMock> {
+    Apds9960Async::new(I2cMock::new(transactions))
+}
+
+fn destroy(sensor: Apds9960Async<I2cMock>) {
+    sensor.destroy().done();
+}
+
+#[test]
+fn can_create() {
+    let sensor = new(&[]);
+    destroy(sensor);
+}
+
+#[test]
+fn keeps_enable_flags() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::ENABLE, BitFlags::PON]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::ENABLE, BitFlags::PON | BitFlags::PEN],
+        ),
+    ];
+    let mut sensor = new(&trans);
+    block_on(sensor.enable()).unwrap();
+    block_on(sensor.enable_proximity()).unwrap();
+    destroy(sensor);
+}
+
+#[test]
+fn can_enable_light_saturation_interrupts() {
+    let trans = [I2cTrans::write(
+        DEV_ADDR,
+        vec![Register::CONFIG2, DEFAULT_CONFIG2 | BitFlags::CPSIEN],
+    )];
+    let mut sensor = new(&trans);
+    block_on(sensor.enable_light_saturation_interrupts()).unwrap();
+    destroy(sensor);
+}
+
+#[test]
+fn can_set_light_low_threshold() {
+    let trans = [I2cTrans::write(DEV_ADDR, vec![Register::AILTL, 0xCD, 0xAB])];
+    let mut sensor = new(&trans);
+    block_on(sensor.set_light_low_threshold(0xABCD)).unwrap();
+    destroy(sensor);
+}
+
+#[test]
+fn can_read_id() {
+    let trans = [I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::ID],
+        vec![0xAB],
+    )];
+    let mut sensor = new(&trans);
+    assert_eq!(0xAB, block_on(sensor.read_device_id()).unwrap());
+    destroy(sensor);
+}
+
+#[test]
+fn can_read_proximity() {
+    let trans = [
+        I2cTrans::write_read(DEV_ADDR, vec![Register::STATUS], vec![BitFlags::PVALID]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::PDATA], vec![0x12]),
+    ];
+    let mut sensor = new(&trans);
+    assert_eq!(0x12, block_on(sensor.read_proximity()).unwrap());
+    destroy(sensor);
+}
+
+#[test]
+fn can_read_light() {
+    let trans = [
+        I2cTrans::write_read(DEV_ADDR, vec![Register::STATUS], vec![BitFlags::AVALID]),
+        I2cTrans::write_read(
+            DEV_ADDR,
+            vec![Register::CDATAL],
+            vec![0x34, 0x12, 0x56, 0x34, 0x78, 0x56, 0x9A, 0x78],
+        ),
+    ];
+    let mut sensor = new(&trans);
+    let data = block_on(sensor.read_light()).unwrap();
+    assert_eq!(
+        LightData {
+            clear: 0x1234,
+            red: 0x3456,
+            green: 0x5678,
+            blue: 0x789A
+        },
+        data
+    );
+    destroy(sensor);
+}
+
+#[test]
+fn can_read_gesture_data() {
+    let trans = [
+        I2cTrans::write_read(DEV_ADDR, vec![Register::GSTATUS], vec![BitFlags::GVALID]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::GFLVL], vec![1]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::GFIFO_U], vec![1, 2, 3, 4]),
+    ];
+    let mut sensor = new(&trans);
+    let mut data = [0; 4];
+    block_on(sensor.read_gesture_data(&mut data)).unwrap();
+    assert_eq!([1, 2, 3, 4], data);
+    destroy(sensor);
+}
+
+#[test]
+fn cannot_read_proximity_if_not_valid() {
+    let trans = [I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::STATUS],
+        vec![0],
+    )];
+    let mut sensor = new(&trans);
+    assert_would_block!(block_on(sensor.read_proximity()));
+    destroy(sensor);
+}
+
+#[test]
+fn can_auto_range_light() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::ATIME, 192]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, BitFlags::AGAIN0]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::STATUS], vec![BitFlags::AVALID]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::CDATAL], vec![0; 8]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::STATUS], vec![BitFlags::AVALID]),
+        I2cTrans::write_read(
+            DEV_ADDR,
+            vec![Register::CDATAL],
+            vec![0x30, 0x75, 0, 0, 0, 0, 0, 0],
+        ),
+    ];
+    let mut sensor = new(&trans);
+    let mut auto_range = LightAutoRangeAsync::new();
+    assert_would_block!(block_on(auto_range.read_light(&mut sensor)));
+    assert_would_block!(block_on(auto_range.read_light(&mut sensor)));
+    let sample = block_on(auto_range.read_light(&mut sensor)).unwrap();
+    assert_eq!(30000, sample.data.clear);
+    let expected = LightSettings {
+        integration_cycles: 64,
+        gain: LightGain::X4,
+    };
+    assert_eq!(expected, sample.settings);
+    destroy(sensor);
+}
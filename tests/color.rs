@@ -0,0 +1,118 @@
+use apds9960::{
+    color::{CalibrationMatrix, Chromaticity, Xyz},
+    IrCompensatedLightData, LightData,
+};
+
+const DATA: LightData = LightData {
+    clear: 1000,
+    red: 400,
+    green: 450,
+    blue: 300,
+};
+
+#[test]
+fn default_xyz() {
+    let expected = Xyz {
+        x: 35310,
+        y: 36082,
+        z: 24301,
+    };
+    assert_eq!(expected, CalibrationMatrix::default().xyz(&DATA));
+}
+
+#[test]
+fn custom_matrix_xyz() {
+    let matrix = CalibrationMatrix::new([
+        [100_000, 0, 0, 0],
+        [0, 50_000, 0, 0],
+        [0, 0, -100_000, 100_000],
+    ]);
+    let expected = Xyz {
+        x: 40000,
+        y: 22500,
+        z: 70000,
+    };
+    assert_eq!(expected, matrix.xyz(&DATA));
+}
+
+#[test]
+fn saturated_xyz_does_not_overflow() {
+    let matrix = CalibrationMatrix::new([[i32::MAX; 4], [i32::MIN; 4], [0; 4]]);
+    let data = LightData {
+        clear: 0xFFFF,
+        red: 0xFFFF,
+        green: 0xFFFF,
+        blue: 0xFFFF,
+    };
+    let xyz = matrix.xyz(&data);
+    assert_eq!(i32::MAX, xyz.x);
+    assert_eq!(i32::MIN, xyz.y);
+}
+
+#[test]
+fn chromaticity() {
+    let xyz = Xyz {
+        x: 35310,
+        y: 36082,
+        z: 24301,
+    };
+    let expected = Chromaticity {
+        x: 368_992,
+        y: 377_059,
+    };
+    assert_eq!(Some(expected), xyz.chromaticity());
+}
+
+#[test]
+fn no_chromaticity_for_negative_values() {
+    let xyz = Xyz {
+        x: -2925,
+        y: -1884,
+        z: 3557,
+    };
+    assert_eq!(None, xyz.chromaticity());
+}
+
+#[test]
+fn no_chromaticity_for_zero() {
+    let xyz = Xyz { x: 0, y: 0, z: 0 };
+    assert_eq!(None, xyz.chromaticity());
+}
+
+macro_rules! cct_test {
+    ($name:ident, $x:expr, $y:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let chromaticity = Chromaticity { x: $x, y: $y };
+            assert_eq!($expected, chromaticity.cct());
+        }
+    };
+}
+// Standard illuminants A (2856K), D50 (5003K) and D65 (6504K)
+cct_test!(cct_a, 447_570, 407_450, Some(2857));
+cct_test!(cct_d50, 345_670, 358_500, Some(5002));
+cct_test!(cct_d65, 312_710, 329_020, Some(6503));
+cct_test!(cct_out_of_range, 100_000, 185_800, None);
+cct_test!(cct_too_far, 900_000, 100_000, None);
+
+#[test]
+fn ir_compensated_xyz() {
+    let matrix = CalibrationMatrix::new([
+        [100_000, 0, 0, 0],
+        [0, 100_000, 0, 0],
+        [0, 0, 100_000, 100_000],
+    ]);
+    let data = IrCompensatedLightData {
+        clear: 925,
+        red: 325,
+        green: -375,
+        blue: 225,
+        ir: 75,
+    };
+    let expected = Xyz {
+        x: 32500,
+        y: -37500,
+        z: 115_000,
+    };
+    assert_eq!(expected, matrix.xyz_ir_compensated(&data));
+}
@@ -1,5 +1,7 @@
 use apds9960::Apds9960;
-use hal::i2c::{Mock as I2cMock, Transaction as I2cTrans};
+use core::convert::Infallible;
+use embedded_hal::i2c::{ErrorType, I2c, Operation};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTrans};
 
 pub const DEV_ADDR: u8 = 0x39;
 
@@ -13,7 +15,10 @@ impl Register {
     pub const AIHTL: u8 = 0x86;
     pub const PILT: u8 = 0x89;
     pub const PIHT: u8 = 0x8B;
+    pub const PERS: u8 = 0x8C;
     pub const CONFIG1: u8 = 0x8D;
+    pub const PPULSE: u8 = 0x8E;
+    pub const CONTROL: u8 = 0x8F;
     pub const CONFIG2: u8 = 0x90;
     pub const ID: u8 = 0x92;
     pub const STATUS: u8 = 0x93;
@@ -24,13 +29,17 @@ impl Register {
     pub const PDATA: u8 = 0x9C;
     pub const POFFSET_UR: u8 = 0x9D;
     pub const POFFSET_DL: u8 = 0x9E;
+    pub const CONFIG3: u8 = 0x9F;
     pub const GPENTH: u8 = 0xA0;
     pub const GPEXTH: u8 = 0xA1;
     pub const GCONFIG1: u8 = 0xA2;
+    pub const GCONFIG2: u8 = 0xA3;
     pub const GOFFSET_U: u8 = 0xA4;
     pub const GOFFSET_D: u8 = 0xA5;
-    pub const GOFFSET_L: u8 = 0xA6;
-    pub const GOFFSET_R: u8 = 0xA7;
+    pub const GPULSE: u8 = 0xA6;
+    pub const GOFFSET_L: u8 = 0xA7;
+    pub const GOFFSET_R: u8 = 0xA9;
+    pub const GCONFIG3: u8 = 0xAA;
     pub const GCONFIG4: u8 = 0xAB;
     pub const GFLVL: u8 = 0xAE;
     pub const GSTATUS: u8 = 0xAF;
@@ -39,6 +48,7 @@ impl Register {
     pub const CICLEAR: u8 = 0xE6;
     pub const AICLEAR: u8 = 0xE7;
     pub const GFIFO_U: u8 = 0xFC;
+    pub const GFIFO_R: u8 = 0xFF;
 }
 
 pub struct BitFlags;
@@ -54,30 +64,223 @@ impl BitFlags {
     pub const WLONG: u8 = 1 << 1;
     pub const CPSIEN: u8 = 1 << 6;
     pub const PSIEN: u8 = 1 << 7;
+    pub const LED_BOOST1: u8 = 1 << 5;
+    pub const LED_BOOST0: u8 = 1 << 4;
     pub const AVALID: u8 = 1;
     pub const PVALID: u8 = 1 << 1;
+    pub const GINT: u8 = 1 << 2;
+    pub const AINT: u8 = 1 << 4;
+    pub const PINT: u8 = 1 << 5;
+    pub const PGSAT: u8 = 1 << 6;
+    pub const CPSAT: u8 = 1 << 7;
     pub const GMODE: u8 = 1;
     pub const GIEN: u8 = 1 << 1;
+    pub const GFIFO_CLR: u8 = 1 << 2;
     pub const GVALID: u8 = 1;
     pub const GFOV: u8 = 1 << 1;
     pub const GFIFOTH1: u8 = 1 << 7;
     pub const GFIFOTH0: u8 = 1 << 6;
+    pub const PPLEN1: u8 = 1 << 7;
+    pub const PPLEN0: u8 = 1 << 6;
+    pub const PCMP: u8 = 1 << 5;
+    pub const SAI: u8 = 1 << 4;
+    pub const PMASK_U: u8 = 1 << 3;
+    pub const PMASK_D: u8 = 1 << 2;
+    pub const PMASK_L: u8 = 1 << 1;
+    pub const PMASK_R: u8 = 1;
+    pub const GEXMSK_U: u8 = 1 << 5;
+    pub const GEXMSK_D: u8 = 1 << 4;
+    pub const GEXMSK_L: u8 = 1 << 3;
+    pub const GEXMSK_R: u8 = 1 << 2;
+    pub const GEXPERS1: u8 = 1 << 1;
+    pub const GEXPERS0: u8 = 1;
+    pub const GGAIN1: u8 = 1 << 6;
+    pub const GGAIN0: u8 = 1 << 5;
+    pub const GLDRIVE1: u8 = 1 << 4;
+    pub const GLDRIVE0: u8 = 1 << 3;
+    pub const GDIMS1: u8 = 1 << 1;
+    pub const GDIMS0: u8 = 1;
+    pub const GPLEN1: u8 = 1 << 7;
+    pub const GPLEN0: u8 = 1 << 6;
+    pub const LDRIVE1: u8 = 1 << 7;
+    pub const LDRIVE0: u8 = 1 << 6;
+    pub const PGAIN1: u8 = 1 << 3;
+    pub const PGAIN0: u8 = 1 << 2;
+    pub const AGAIN1: u8 = 1 << 1;
+    pub const AGAIN0: u8 = 1;
 }
 
 #[allow(unused)]
 pub const DEFAULT_CONFIG1: u8 = 0x40;
 
+#[allow(unused)]
+pub const DEFAULT_PPULSE: u8 = 0x40;
+
+#[allow(unused)]
+pub const DEFAULT_GPULSE: u8 = 0x40;
+
 #[allow(unused)]
 pub const DEFAULT_CONFIG2: u8 = 1;
 
+#[allow(unused)]
 pub fn new(transactions: &[I2cTrans]) -> Apds9960<I2cMock> {
     Apds9960::new(I2cMock::new(transactions))
 }
 
+#[allow(unused)]
 pub fn destroy(sensor: Apds9960<I2cMock>) {
     sensor.destroy().done();
 }
 
+/// Photodiode offset of a sign-magnitude register value.
+fn offset(value: u8) -> i16 {
+    let magnitude = i16::from(value & 0x7F);
+    if value & 0x80 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Number of datasets after which the simulated device leaves gesture mode.
+const DATASETS_PER_GESTURE: u8 = 3;
+
+/// Simulated device with crosstalk on the photodiodes and no target in front of it.
+///
+/// The proximity data is the sum of the crosstalk minus the offset of each unmasked
+/// photodiode pair. When one pair is masked, the value is doubled if the gain compensation
+/// is enabled.
+///
+/// A gesture dataset is available in the FIFO whenever the device is in gesture mode.
+/// The device leaves gesture mode after some datasets.
+#[allow(unused)]
+pub struct FakeDevice {
+    pub registers: [u8; 256],
+    /// Up/right and down/left proximity crosstalk.
+    pub proximity_crosstalk: [i16; 2],
+    /// Up, down, left and right gesture crosstalk.
+    pub gesture_crosstalk: [i16; 4],
+    /// Whether the device provides new data.
+    pub data_available: bool,
+    datasets: u8,
+}
+
+#[allow(unused)]
+impl FakeDevice {
+    pub fn new() -> Self {
+        FakeDevice {
+            registers: [0; 256],
+            proximity_crosstalk: [0; 2],
+            gesture_crosstalk: [0; 4],
+            data_available: true,
+            datasets: 0,
+        }
+    }
+
+    fn register(&self, register: u8) -> u8 {
+        self.registers[usize::from(register)]
+    }
+
+    fn proximity(&self) -> u8 {
+        let masks = self.register(Register::CONFIG3);
+        let pairs = [
+            (BitFlags::PMASK_U | BitFlags::PMASK_R, Register::POFFSET_UR),
+            (BitFlags::PMASK_D | BitFlags::PMASK_L, Register::POFFSET_DL),
+        ];
+        let mut value = 0;
+        for ((mask, offset_register), crosstalk) in pairs.iter().zip(self.proximity_crosstalk) {
+            if masks & mask != *mask {
+                let offset = offset(self.register(*offset_register));
+                value += (crosstalk - offset).max(0);
+            }
+        }
+        let compensated = masks & BitFlags::PCMP != 0 && (masks & 0b1111).count_ones() == 2;
+        if compensated {
+            value *= 2;
+        }
+        value.min(255) as u8
+    }
+
+    fn gesture_data(&mut self, register: u8) -> u8 {
+        let channel = usize::from(register - Register::GFIFO_U);
+        let offset_register = [
+            Register::GOFFSET_U,
+            Register::GOFFSET_D,
+            Register::GOFFSET_L,
+            Register::GOFFSET_R,
+        ][channel];
+        let offset = offset(self.register(offset_register));
+        if register == Register::GFIFO_R {
+            self.datasets += 1;
+            if self.datasets == DATASETS_PER_GESTURE {
+                self.datasets = 0;
+                self.registers[usize::from(Register::GCONFIG4)] &= !BitFlags::GMODE;
+            }
+        }
+        (self.gesture_crosstalk[channel] - offset).clamp(0, 255) as u8
+    }
+
+    fn read_register(&mut self, register: u8) -> u8 {
+        let gesture_mode = self.register(Register::GCONFIG4) & BitFlags::GMODE != 0;
+        match register {
+            Register::STATUS if self.data_available => BitFlags::PVALID,
+            Register::STATUS => 0,
+            Register::PDATA => self.proximity(),
+            Register::GFLVL => u8::from(self.data_available && gesture_mode),
+            Register::GFIFO_U..=Register::GFIFO_R => self.gesture_data(register),
+            _ => self.register(register),
+        }
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) {
+        let value = if register == Register::GCONFIG4 {
+            // Cleared by the device itself.
+            value & !BitFlags::GFIFO_CLR
+        } else {
+            value
+        };
+        self.registers[usize::from(register)] = value;
+    }
+}
+
+impl ErrorType for FakeDevice {
+    type Error = Infallible;
+}
+
+impl I2c for FakeDevice {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        assert_eq!(DEV_ADDR, address);
+        let mut pointer = 0;
+        for operation in operations {
+            match operation {
+                Operation::Write(data) => {
+                    pointer = data[0];
+                    for value in &data[1..] {
+                        self.write_register(pointer, *value);
+                        pointer += 1;
+                    }
+                }
+                Operation::Read(data) => {
+                    for value in data.iter_mut() {
+                        *value = self.read_register(pointer);
+                        // The gesture FIFO registers wrap around.
+                        pointer = if pointer == Register::GFIFO_R {
+                            Register::GFIFO_U
+                        } else {
+                            pointer + 1
+                        };
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 #[macro_export]
 macro_rules! empty_write_test {
     ($name:ident, $method:ident, $reg:ident) => {
@@ -1,9 +1,10 @@
-extern crate apds9960;
-use apds9960::GestureDataThreshold;
-extern crate embedded_hal_mock as hal;
-use hal::i2c::Transaction as I2cTrans;
+use apds9960::{
+    Error, GestureCalibration, GestureDataThreshold, GestureDataset, GestureDimensions,
+    GestureExitPersistence, GestureGain, GesturePulseLength, GestureWaitTime, LedDrive,
+};
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTrans;
 mod common;
-use common::{destroy, new, BitFlags, Register, DEV_ADDR};
+use common::{destroy, new, BitFlags, Register, DEFAULT_GPULSE, DEV_ADDR};
 
 write_test!(can_enable_gesture, enable_gesture, ENABLE, BitFlags::GEN);
 write_test!(can_disable_gesture, disable_gesture, ENABLE, 0);
@@ -21,6 +22,95 @@ write_test!(
     BitFlags::GIEN
 );
 write_test!(dis_gesture_int, disable_gesture_interrupts, GCONFIG4, 0);
+macro_rules! clear_gfifo_test {
+    ($name:ident, $method:ident) => {
+        #[test]
+        fn $name() {
+            let trans = [
+                I2cTrans::write_read(DEV_ADDR, vec![Register::GCONFIG4], vec![0]),
+                I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG4, BitFlags::GFIFO_CLR]),
+            ];
+            let mut sensor = new(&trans);
+            sensor.$method().unwrap();
+            destroy(sensor);
+        }
+    };
+}
+clear_gfifo_test!(can_clear_gfifo, clear_gesture_fifo);
+clear_gfifo_test!(can_clear_gesture_int, clear_gesture_interrupt);
+
+#[test]
+fn gfifo_clr_is_not_kept() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG4, BitFlags::GIEN]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::GCONFIG4], vec![BitFlags::GIEN]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::GCONFIG4, BitFlags::GIEN | BitFlags::GFIFO_CLR],
+        ),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::GCONFIG4, BitFlags::GIEN | BitFlags::GMODE],
+        ),
+    ];
+    let mut sensor = new(&trans);
+    sensor.enable_gesture_interrupts().unwrap();
+    sensor.clear_gesture_fifo().unwrap();
+    sensor.enable_gesture_mode().unwrap();
+    destroy(sensor);
+}
+
+#[test]
+fn clearing_gfifo_does_not_enter_gesture_mode_again() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG4, BitFlags::GMODE]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::GCONFIG4], vec![0]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG4, BitFlags::GFIFO_CLR]),
+    ];
+    let mut sensor = new(&trans);
+    sensor.enable_gesture_mode().unwrap();
+    sensor.clear_gesture_fifo().unwrap();
+    destroy(sensor);
+}
+
+#[test]
+fn clearing_gfifo_keeps_gesture_mode() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG4, BitFlags::GMODE]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::GCONFIG4], vec![BitFlags::GMODE]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::GCONFIG4, BitFlags::GMODE | BitFlags::GFIFO_CLR],
+        ),
+    ];
+    let mut sensor = new(&trans);
+    sensor.enable_gesture_mode().unwrap();
+    sensor.clear_gesture_fifo().unwrap();
+    destroy(sensor);
+}
+
+read_test!(
+    gmode_enabled,
+    is_gesture_mode_enabled,
+    true,
+    GCONFIG4,
+    BitFlags::GMODE
+);
+read_test!(gmode_disabled, is_gesture_mode_enabled, false, GCONFIG4, 0);
+
+#[test]
+fn gmode_read_back_is_kept() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG4, BitFlags::GMODE]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::GCONFIG4], vec![0]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG4, BitFlags::GIEN]),
+    ];
+    let mut sensor = new(&trans);
+    sensor.enable_gesture_mode().unwrap();
+    assert!(!sensor.is_gesture_mode_enabled().unwrap());
+    sensor.enable_gesture_interrupts().unwrap();
+    destroy(sensor);
+}
 write_test!(
     can_set_gprox_entry_th,
     set_gesture_proximity_entry_threshold,
@@ -36,6 +126,220 @@ write_test!(
     0xAB
 );
 
+macro_rules! set_test {
+    ($name:ident, $method:ident, $reg:ident, $type:ident, $variant:ident, $value:expr) => {
+        write_test!($name, $method, $reg, $value, $type::$variant);
+    };
+}
+
+set_test!(set_ggain_1, set_gesture_gain, GCONFIG2, GestureGain, X1, 0);
+set_test!(
+    set_ggain_2,
+    set_gesture_gain,
+    GCONFIG2,
+    GestureGain,
+    X2,
+    BitFlags::GGAIN0
+);
+set_test!(
+    set_ggain_4,
+    set_gesture_gain,
+    GCONFIG2,
+    GestureGain,
+    X4,
+    BitFlags::GGAIN1
+);
+set_test!(
+    set_ggain_8,
+    set_gesture_gain,
+    GCONFIG2,
+    GestureGain,
+    X8,
+    BitFlags::GGAIN1 | BitFlags::GGAIN0
+);
+
+set_test!(
+    set_gldrive_100,
+    set_gesture_led_drive,
+    GCONFIG2,
+    LedDrive,
+    Ma100,
+    0
+);
+set_test!(
+    set_gldrive_50,
+    set_gesture_led_drive,
+    GCONFIG2,
+    LedDrive,
+    Ma50,
+    BitFlags::GLDRIVE0
+);
+set_test!(
+    set_gldrive_25,
+    set_gesture_led_drive,
+    GCONFIG2,
+    LedDrive,
+    Ma25,
+    BitFlags::GLDRIVE1
+);
+set_test!(
+    set_gldrive_12_5,
+    set_gesture_led_drive,
+    GCONFIG2,
+    LedDrive,
+    Ma12_5,
+    BitFlags::GLDRIVE1 | BitFlags::GLDRIVE0
+);
+
+macro_rules! set_gwtime_test {
+    ($name:ident, $variant:ident, $value:expr) => {
+        set_test!(
+            $name,
+            set_gesture_wait_time,
+            GCONFIG2,
+            GestureWaitTime,
+            $variant,
+            $value
+        );
+    };
+}
+set_gwtime_test!(set_gwtime_0, Ms0, 0);
+set_gwtime_test!(set_gwtime_2_8, Ms2_8, 1);
+set_gwtime_test!(set_gwtime_5_6, Ms5_6, 2);
+set_gwtime_test!(set_gwtime_8_4, Ms8_4, 3);
+set_gwtime_test!(set_gwtime_14, Ms14, 4);
+set_gwtime_test!(set_gwtime_22_4, Ms22_4, 5);
+set_gwtime_test!(set_gwtime_30_8, Ms30_8, 6);
+set_gwtime_test!(set_gwtime_39_2, Ms39_2, 7);
+
+#[test]
+fn gconfig2_settings_are_kept() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG2, BitFlags::GGAIN1]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::GCONFIG2, BitFlags::GGAIN1 | BitFlags::GLDRIVE0],
+        ),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![
+                Register::GCONFIG2,
+                BitFlags::GGAIN1 | BitFlags::GLDRIVE0 | 5,
+            ],
+        ),
+    ];
+    let mut sensor = new(&trans);
+    sensor.set_gesture_gain(GestureGain::X4).unwrap();
+    sensor.set_gesture_led_drive(LedDrive::Ma50).unwrap();
+    sensor
+        .set_gesture_wait_time(GestureWaitTime::Ms22_4)
+        .unwrap();
+    destroy(sensor);
+}
+
+set_test!(
+    set_gdims_all,
+    set_gesture_dimensions,
+    GCONFIG3,
+    GestureDimensions,
+    All,
+    0
+);
+set_test!(
+    set_gdims_ud,
+    set_gesture_dimensions,
+    GCONFIG3,
+    GestureDimensions,
+    UpDown,
+    BitFlags::GDIMS0
+);
+set_test!(
+    set_gdims_lr,
+    set_gesture_dimensions,
+    GCONFIG3,
+    GestureDimensions,
+    LeftRight,
+    BitFlags::GDIMS1
+);
+
+set_test!(
+    set_gplen_4,
+    set_gesture_pulse_length,
+    GPULSE,
+    GesturePulseLength,
+    Us4,
+    0
+);
+set_test!(
+    set_gplen_8,
+    set_gesture_pulse_length,
+    GPULSE,
+    GesturePulseLength,
+    Us8,
+    BitFlags::GPLEN0
+);
+set_test!(
+    set_gplen_16,
+    set_gesture_pulse_length,
+    GPULSE,
+    GesturePulseLength,
+    Us16,
+    BitFlags::GPLEN1
+);
+set_test!(
+    set_gplen_32,
+    set_gesture_pulse_length,
+    GPULSE,
+    GesturePulseLength,
+    Us32,
+    BitFlags::GPLEN1 | BitFlags::GPLEN0
+);
+
+write_test!(
+    set_gpulse_1,
+    set_gesture_pulse_count,
+    GPULSE,
+    DEFAULT_GPULSE,
+    1
+);
+write_test!(
+    set_gpulse_64,
+    set_gesture_pulse_count,
+    GPULSE,
+    DEFAULT_GPULSE | 63,
+    64
+);
+
+macro_rules! invalid_gpulse_test {
+    ($name:ident, $count:expr) => {
+        #[test]
+        fn $name() {
+            let mut sensor = new(&[]);
+            match sensor.set_gesture_pulse_count($count) {
+                Err(Error::InvalidInputData) => (),
+                _ => panic!("InvalidInputData error not returned."),
+            }
+            destroy(sensor);
+        }
+    };
+}
+invalid_gpulse_test!(cannot_set_gpulse_0, 0);
+invalid_gpulse_test!(cannot_set_gpulse_65, 65);
+
+#[test]
+fn gpulse_settings_are_kept() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::GPULSE, BitFlags::GPLEN1]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GPULSE, BitFlags::GPLEN1 | 9]),
+    ];
+    let mut sensor = new(&trans);
+    sensor
+        .set_gesture_pulse_length(GesturePulseLength::Us16)
+        .unwrap();
+    sensor.set_gesture_pulse_count(10).unwrap();
+    destroy(sensor);
+}
+
 write_test!(set_goffset_u, set_gesture_up_offset, GOFFSET_U, 55, 55);
 write_test!(set_goffset_d, set_gesture_down_offset, GOFFSET_D, 55, 55);
 write_test!(set_goffset_l, set_gesture_left_offset, GOFFSET_L, 55, 55);
@@ -43,19 +347,53 @@ write_test!(set_goffset_r, set_gesture_right_offset, GOFFSET_R, 55, 55);
 
 #[test]
 fn can_set_goffsets() {
-    let data = vec![
-        Register::GOFFSET_U,
-        55,
-        i8::from(-56) as u8,
-        100,
-        i8::from(-101) as u8,
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::GOFFSET_U, 55, 0x80 | 56]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GOFFSET_L, 100]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GOFFSET_R, 0x80 | 101]),
     ];
-    let trans = [I2cTrans::write(DEV_ADDR, data)];
     let mut sensor = new(&trans);
     sensor.set_gesture_offsets(55, -56, 100, -101).unwrap();
     destroy(sensor);
 }
 
+macro_rules! invalid_goffset_test {
+    ($name:ident, $sensor:ident => $call:expr) => {
+        #[test]
+        fn $name() {
+            let mut $sensor = new(&[]);
+            match $call {
+                Err(Error::InvalidInputData) => (),
+                _ => panic!("InvalidInputData error not returned."),
+            }
+            destroy($sensor);
+        }
+    };
+}
+invalid_goffset_test!(cannot_set_goffset_u_min, s => s.set_gesture_up_offset(-128));
+invalid_goffset_test!(cannot_set_goffset_d_min, s => s.set_gesture_down_offset(-128));
+invalid_goffset_test!(cannot_set_goffset_l_min, s => s.set_gesture_left_offset(-128));
+invalid_goffset_test!(cannot_set_goffset_r_min, s => s.set_gesture_right_offset(-128));
+invalid_goffset_test!(cannot_set_goffsets_min, s => s.set_gesture_offsets(0, 0, 0, -128));
+
+#[test]
+fn can_set_gesture_calibration() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::GOFFSET_U, 55, 0x80 | 56]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GOFFSET_L, 100]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GOFFSET_R, 0x80 | 101]),
+    ];
+    let mut sensor = new(&trans);
+    let calibration = GestureCalibration {
+        up_offset: 55,
+        down_offset: -56,
+        left_offset: 100,
+        right_offset: -101,
+    };
+    sensor.set_gesture_calibration(calibration).unwrap();
+    destroy(sensor);
+}
+
 read_test!(
     can_read_gvalid_true,
     is_gesture_data_valid,
@@ -106,6 +444,95 @@ set_gdata_level_th_test!(
     BitFlags::GFIFOTH1 | BitFlags::GFIFOTH0
 );
 
+write_test!(
+    en_gexmsk_u,
+    enable_gesture_exit_up_mask,
+    GCONFIG1,
+    BitFlags::GEXMSK_U
+);
+write_test!(dis_gexmsk_u, disable_gesture_exit_up_mask, GCONFIG1, 0);
+write_test!(
+    en_gexmsk_d,
+    enable_gesture_exit_down_mask,
+    GCONFIG1,
+    BitFlags::GEXMSK_D
+);
+write_test!(dis_gexmsk_d, disable_gesture_exit_down_mask, GCONFIG1, 0);
+write_test!(
+    en_gexmsk_l,
+    enable_gesture_exit_left_mask,
+    GCONFIG1,
+    BitFlags::GEXMSK_L
+);
+write_test!(dis_gexmsk_l, disable_gesture_exit_left_mask, GCONFIG1, 0);
+write_test!(
+    en_gexmsk_r,
+    enable_gesture_exit_right_mask,
+    GCONFIG1,
+    BitFlags::GEXMSK_R
+);
+write_test!(dis_gexmsk_r, disable_gesture_exit_right_mask, GCONFIG1, 0);
+
+set_test!(
+    set_gexpers_1,
+    set_gesture_exit_persistence,
+    GCONFIG1,
+    GestureExitPersistence,
+    First,
+    0
+);
+set_test!(
+    set_gexpers_2,
+    set_gesture_exit_persistence,
+    GCONFIG1,
+    GestureExitPersistence,
+    Second,
+    BitFlags::GEXPERS0
+);
+set_test!(
+    set_gexpers_4,
+    set_gesture_exit_persistence,
+    GCONFIG1,
+    GestureExitPersistence,
+    Fourth,
+    BitFlags::GEXPERS1
+);
+set_test!(
+    set_gexpers_7,
+    set_gesture_exit_persistence,
+    GCONFIG1,
+    GestureExitPersistence,
+    Seventh,
+    BitFlags::GEXPERS1 | BitFlags::GEXPERS0
+);
+
+#[test]
+fn gconfig1_settings_are_kept() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG1, BitFlags::GFIFOTH1]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::GCONFIG1, BitFlags::GFIFOTH1 | BitFlags::GEXMSK_L],
+        ),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![
+                Register::GCONFIG1,
+                BitFlags::GFIFOTH1 | BitFlags::GEXMSK_L | BitFlags::GEXPERS1,
+            ],
+        ),
+    ];
+    let mut sensor = new(&trans);
+    sensor
+        .set_gesture_data_level_threshold(GestureDataThreshold::Th8)
+        .unwrap();
+    sensor.enable_gesture_exit_left_mask().unwrap();
+    sensor
+        .set_gesture_exit_persistence(GestureExitPersistence::Fourth)
+        .unwrap();
+    destroy(sensor);
+}
+
 macro_rules! read_data_test {
     ($name:ident, $method:ident, $expected:expr, $data_size:expr, $($reg:ident, [$($value:expr),*]),*) => {
         #[test]
@@ -183,3 +610,33 @@ fn cannot_read_gesture_if_not_valid() {
     assert_would_block!(sensor.read_gesture_data(&mut [0; 4]));
     destroy(sensor);
 }
+
+#[test]
+fn read_gesture_dataset_would_block() {
+    let trans = [I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::GFLVL],
+        vec![0],
+    )];
+    let mut sensor = new(&trans);
+    assert_would_block!(sensor.read_gesture_dataset());
+    destroy(sensor);
+}
+
+#[test]
+fn can_read_gesture_dataset() {
+    let trans = [
+        I2cTrans::write_read(DEV_ADDR, vec![Register::GFLVL], vec![3]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::GFIFO_U], vec![1, 2, 3, 4]),
+    ];
+    let mut sensor = new(&trans);
+    let dataset = sensor.read_gesture_dataset().unwrap();
+    let expected = GestureDataset {
+        up: 1,
+        down: 2,
+        left: 3,
+        right: 4,
+    };
+    assert_eq!(expected, dataset);
+    destroy(sensor);
+}
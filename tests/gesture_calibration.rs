@@ -0,0 +1,94 @@
+use apds9960::{Apds9960, Error, GestureCalibration};
+mod common;
+use common::{BitFlags, FakeDevice, Register};
+
+fn new(crosstalk: [i16; 4]) -> Apds9960<FakeDevice> {
+    let mut device = FakeDevice::new();
+    device.gesture_crosstalk = crosstalk;
+    Apds9960::new(device)
+}
+
+macro_rules! calibration_test {
+    ($name:ident, $crosstalk:expr, $up:expr, $down:expr, $left:expr, $right:expr) => {
+        #[test]
+        fn $name() {
+            let mut sensor = new($crosstalk);
+            let expected = GestureCalibration {
+                up_offset: $up,
+                down_offset: $down,
+                left_offset: $left,
+                right_offset: $right,
+            };
+            assert_eq!(expected, sensor.calibrate_gesture_offsets().unwrap());
+            let registers = sensor.destroy().registers;
+            assert_eq!($up as u8, registers[usize::from(Register::GOFFSET_U)]);
+            assert_eq!($down as u8, registers[usize::from(Register::GOFFSET_D)]);
+            assert_eq!($left as u8, registers[usize::from(Register::GOFFSET_L)]);
+            assert_eq!($right as u8, registers[usize::from(Register::GOFFSET_R)]);
+            assert_eq!(0, registers[usize::from(Register::GCONFIG4)]);
+            assert_eq!(0, registers[usize::from(Register::ENABLE)]);
+        }
+    };
+}
+calibration_test!(no_crosstalk, [0, 0, 0, 0], 0, 0, 0, 0);
+calibration_test!(crosstalk, [40, 12, 25, 3], 39, 11, 24, 2);
+calibration_test!(crosstalk_one_channel, [0, 0, 90, 0], 0, 0, 89, 0);
+calibration_test!(crosstalk_max, [200, 128, 1, 60], 127, 127, 0, 59);
+
+#[test]
+fn gesture_configuration_and_enable_are_restored() {
+    let mut sensor = new([20, 30, 10, 5]);
+    sensor.enable().unwrap();
+    sensor.enable_proximity().unwrap();
+    sensor.enable_gesture_interrupts().unwrap();
+    sensor.calibrate_gesture_offsets().unwrap();
+    sensor.enable_gesture_mode().unwrap();
+    let registers = sensor.destroy().registers;
+    assert_eq!(
+        BitFlags::GIEN | BitFlags::GMODE,
+        registers[usize::from(Register::GCONFIG4)]
+    );
+    assert_eq!(
+        BitFlags::PON | BitFlags::PEN,
+        registers[usize::from(Register::ENABLE)]
+    );
+}
+
+#[test]
+fn gesture_mode_left_by_device_is_not_restored() {
+    let mut sensor = new([20, 30, 10, 5]);
+    sensor.enable().unwrap();
+    sensor.enable_gesture().unwrap();
+    sensor.enable_gesture_mode().unwrap();
+    // The device leaves gesture mode after some datasets.
+    while sensor.read_gesture_dataset().is_ok() {}
+    sensor.calibrate_gesture_offsets().unwrap();
+    let registers = sensor.destroy().registers;
+    assert_eq!(0, registers[usize::from(Register::GCONFIG4)]);
+    assert_eq!(
+        BitFlags::PON | BitFlags::GEN,
+        registers[usize::from(Register::ENABLE)]
+    );
+}
+
+#[test]
+fn times_out_and_restores_state() {
+    let mut device = FakeDevice::new();
+    device.gesture_crosstalk = [20, 30, 10, 5];
+    device.data_available = false;
+    let mut sensor = Apds9960::new(device);
+    sensor.enable().unwrap();
+    sensor.enable_gesture_interrupts().unwrap();
+    sensor.set_gesture_offsets(1, -2, 3, -4).unwrap();
+    match sensor.calibrate_gesture_offsets() {
+        Err(Error::Timeout) => (),
+        _ => panic!("Timeout error not returned."),
+    }
+    let registers = sensor.destroy().registers;
+    assert_eq!(BitFlags::GIEN, registers[usize::from(Register::GCONFIG4)]);
+    assert_eq!(BitFlags::PON, registers[usize::from(Register::ENABLE)]);
+    assert_eq!(1, registers[usize::from(Register::GOFFSET_U)]);
+    assert_eq!(0x80 | 2, registers[usize::from(Register::GOFFSET_D)]);
+    assert_eq!(3, registers[usize::from(Register::GOFFSET_L)]);
+    assert_eq!(0x80 | 4, registers[usize::from(Register::GOFFSET_R)]);
+}
@@ -0,0 +1,253 @@
+use apds9960::{Gesture, GestureDataset, GestureOrientation, GestureRecognizer};
+
+const UP: [u8; 12] = [120, 40, 80, 80, 90, 90, 90, 90, 40, 120, 80, 80];
+const DOWN: [u8; 12] = [40, 120, 80, 80, 90, 90, 90, 90, 120, 40, 80, 80];
+const LEFT: [u8; 12] = [80, 80, 120, 40, 90, 90, 90, 90, 80, 80, 40, 120];
+const RIGHT: [u8; 12] = [80, 80, 40, 120, 90, 90, 90, 90, 80, 80, 120, 40];
+const END: [u8; 4] = [2, 3, 1, 0];
+
+fn recognize(recognizer: &mut GestureRecognizer, data: &[u8]) -> Option<Gesture> {
+    assert_eq!(None, recognizer.process(data));
+    recognizer.process(&END)
+}
+
+macro_rules! gesture_test {
+    ($name:ident, $orientation:ident, $data:expr, $expected:ident) => {
+        #[test]
+        fn $name() {
+            let mut recognizer = GestureRecognizer::new();
+            recognizer.set_orientation(GestureOrientation::$orientation);
+            assert_eq!(Some(Gesture::$expected), recognize(&mut recognizer, &$data));
+        }
+    };
+}
+gesture_test!(up, Normal, UP, Up);
+gesture_test!(down, Normal, DOWN, Down);
+gesture_test!(left, Normal, LEFT, Left);
+gesture_test!(right, Normal, RIGHT, Right);
+gesture_test!(up_rotated90, Rotated90, UP, Right);
+gesture_test!(right_rotated90, Rotated90, RIGHT, Down);
+gesture_test!(up_rotated180, Rotated180, UP, Down);
+gesture_test!(left_rotated180, Rotated180, LEFT, Right);
+gesture_test!(up_rotated270, Rotated270, UP, Left);
+gesture_test!(down_rotated270, Rotated270, DOWN, Right);
+
+#[test]
+fn noise_is_ignored() {
+    let mut recognizer = GestureRecognizer::new();
+    assert_eq!(None, recognizer.process(&[10, 0, 5, 8, 0, 0, 0, 0]));
+    assert_eq!(None, recognizer.finish());
+}
+
+#[test]
+fn dominant_axis_is_used() {
+    let mut recognizer = GestureRecognizer::new();
+    let data = [100, 60, 40, 120, 60, 100, 120, 40];
+    assert_eq!(Some(Gesture::Right), recognize(&mut recognizer, &data));
+}
+
+#[test]
+fn weak_movement_is_not_recognized() {
+    let mut recognizer = GestureRecognizer::new();
+    let data = [90, 70, 80, 80, 70, 90, 80, 80];
+    assert_eq!(None, recognize(&mut recognizer, &data));
+    recognizer.set_sensitivity(20);
+    assert_eq!(Some(Gesture::Up), recognize(&mut recognizer, &data));
+}
+
+#[test]
+fn noise_threshold_can_be_set() {
+    let mut recognizer = GestureRecognizer::new();
+    recognizer.set_noise_threshold(100);
+    assert_eq!(None, recognize(&mut recognizer, &UP));
+}
+
+#[test]
+fn can_finish_gesture() {
+    let mut recognizer = GestureRecognizer::new();
+    assert_eq!(None, recognizer.process(&LEFT));
+    assert_eq!(Some(Gesture::Left), recognizer.finish());
+    assert_eq!(None, recognizer.finish());
+}
+
+#[test]
+fn can_reset() {
+    let mut recognizer = GestureRecognizer::new();
+    assert_eq!(None, recognizer.process(&LEFT));
+    recognizer.reset();
+    assert_eq!(None, recognizer.finish());
+}
+
+#[test]
+fn last_gesture_within_data_is_returned() {
+    let mut recognizer = GestureRecognizer::new();
+    let mut data = [0; 32];
+    data[..12].copy_from_slice(&UP);
+    data[12..16].copy_from_slice(&END);
+    data[16..28].copy_from_slice(&RIGHT);
+    data[28..].copy_from_slice(&END);
+    assert_eq!(Some(Gesture::Right), recognizer.process(&data));
+}
+
+#[test]
+fn incomplete_dataset_is_ignored() {
+    let mut recognizer = GestureRecognizer::new();
+    assert_eq!(None, recognizer.process(&[0, 0, 0]));
+    assert_eq!(None, recognizer.finish());
+}
+
+/// Process balanced datasets with the given value on every photodiode.
+fn process_balanced(
+    recognizer: &mut GestureRecognizer,
+    values: impl Iterator<Item = u8>,
+) -> Option<Gesture> {
+    let mut gesture = None;
+    for value in values {
+        if let Some(recognized) = recognizer.process_dataset(GestureDataset::from([value; 4])) {
+            gesture = Some(recognized);
+        }
+    }
+    gesture
+}
+
+fn approach() -> impl Iterator<Item = u8> {
+    (0..12).map(|i| 20 + 5 * i)
+}
+
+fn withdrawal() -> impl Iterator<Item = u8> {
+    approach().collect::<Vec<_>>().into_iter().rev()
+}
+
+#[test]
+fn near() {
+    let mut recognizer = GestureRecognizer::new();
+    assert_eq!(None, process_balanced(&mut recognizer, approach()));
+    assert_eq!(Some(Gesture::Near), recognizer.process(&END));
+}
+
+#[test]
+fn far() {
+    let mut recognizer = GestureRecognizer::new();
+    assert_eq!(None, process_balanced(&mut recognizer, withdrawal()));
+    assert_eq!(Some(Gesture::Far), recognizer.finish());
+}
+
+#[test]
+fn depth_is_not_affected_by_orientation() {
+    let mut recognizer = GestureRecognizer::new();
+    recognizer.set_orientation(GestureOrientation::Rotated90);
+    process_balanced(&mut recognizer, approach());
+    assert_eq!(Some(Gesture::Near), recognizer.finish());
+}
+
+#[test]
+fn short_depth_movement_is_not_recognized() {
+    let mut recognizer = GestureRecognizer::new();
+    process_balanced(&mut recognizer, approach().take(9));
+    assert_eq!(None, recognizer.finish());
+    recognizer.set_depth_duration(9);
+    process_balanced(&mut recognizer, approach().take(9));
+    assert_eq!(Some(Gesture::Near), recognizer.finish());
+}
+
+#[test]
+fn depth_threshold_can_be_set() {
+    let mut recognizer = GestureRecognizer::new();
+    recognizer.set_depth_threshold(200);
+    process_balanced(&mut recognizer, approach());
+    assert_eq!(None, recognizer.finish());
+}
+
+#[test]
+fn swipe_energy_is_not_a_depth_gesture() {
+    let mut recognizer = GestureRecognizer::new();
+    process_balanced(&mut recognizer, approach().chain(withdrawal()));
+    assert_eq!(None, recognizer.finish());
+}
+
+#[test]
+fn proximity_confirms_depth_gesture() {
+    let mut recognizer = GestureRecognizer::new();
+    recognizer.process_proximity(10);
+    process_balanced(&mut recognizer, approach());
+    recognizer.process_proximity(200);
+    assert_eq!(Some(Gesture::Near), recognizer.finish());
+}
+
+#[test]
+fn proximity_contradicts_depth_gesture() {
+    let mut recognizer = GestureRecognizer::new();
+    recognizer.process_proximity(200);
+    process_balanced(&mut recognizer, approach());
+    recognizer.process_proximity(10);
+    assert_eq!(None, recognizer.finish());
+    // the last reading is used as the reference for the next gesture
+    process_balanced(&mut recognizer, withdrawal());
+    recognizer.process_proximity(5);
+    assert_eq!(Some(Gesture::Far), recognizer.finish());
+}
+
+#[test]
+fn hover() {
+    let mut recognizer = GestureRecognizer::new();
+    assert_eq!(None, process_balanced(&mut recognizer, approach()));
+    let still = core::iter::repeat(100);
+    assert_eq!(
+        None,
+        process_balanced(&mut recognizer, still.clone().take(99))
+    );
+    assert_eq!(
+        Some(Gesture::Hover),
+        recognizer.process_dataset(GestureDataset::from([100; 4]))
+    );
+    assert_eq!(None, process_balanced(&mut recognizer, still.take(50)));
+    assert_eq!(None, process_balanced(&mut recognizer, withdrawal()));
+    assert_eq!(None, recognizer.finish());
+}
+
+#[test]
+fn long_hover() {
+    let mut recognizer = GestureRecognizer::new();
+    recognizer.set_hover_duration(1);
+    let still = (0..1_000_000).map(|_| 255);
+    assert_eq!(
+        Some(Gesture::Hover),
+        process_balanced(&mut recognizer, still)
+    );
+    assert_eq!(None, recognizer.finish());
+    recognizer.set_hover_duration(100);
+    assert_eq!(Some(Gesture::Up), recognize(&mut recognizer, &UP));
+}
+
+#[test]
+fn hover_duration_and_threshold_can_be_set() {
+    let mut recognizer = GestureRecognizer::new();
+    recognizer.set_hover_duration(5);
+    let unsteady = [60, 80, 60, 80, 60, 80].into_iter();
+    assert_eq!(None, process_balanced(&mut recognizer, unsteady.clone()));
+    recognizer.reset();
+    recognizer.set_hover_threshold(40);
+    assert_eq!(
+        Some(Gesture::Hover),
+        process_balanced(&mut recognizer, unsteady)
+    );
+}
+
+#[test]
+fn can_process_dataset() {
+    let mut recognizer = GestureRecognizer::new();
+    let entry = GestureDataset {
+        up: 80,
+        down: 80,
+        left: 120,
+        right: 40,
+    };
+    let exit = GestureDataset {
+        left: 40,
+        right: 120,
+        ..entry
+    };
+    assert_eq!(None, recognizer.process_dataset(entry));
+    assert_eq!(None, recognizer.process_dataset(exit));
+    assert_eq!(Some(Gesture::Left), recognizer.finish());
+}
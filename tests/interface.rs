@@ -1,8 +1,14 @@
-extern crate apds9960;
-extern crate embedded_hal_mock as hal;
-use hal::i2c::Transaction as I2cTrans;
+use apds9960::{
+    DeviceStatus, DeviceVariant, Error, GestureDimensions, GestureExitPersistence, GestureWaitTime,
+    LightGain, ProximityGain,
+};
+use core::time::Duration;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTrans;
 mod common;
-use common::{destroy, new, BitFlags, Register, DEFAULT_CONFIG1, DEV_ADDR};
+use common::{
+    destroy, new, BitFlags, Register, DEFAULT_CONFIG1, DEFAULT_CONFIG2, DEFAULT_GPULSE,
+    DEFAULT_PPULSE, DEV_ADDR,
+};
 
 #[test]
 fn can_create() {
@@ -15,6 +21,220 @@ write_test!(can_disable, disable, ENABLE, 0);
 
 read_test!(can_read_id, read_device_id, 0xAB, ID, 0xAB);
 
+macro_rules! probe_test {
+    ($name:ident, $id:expr, $variant:ident) => {
+        #[test]
+        fn $name() {
+            let trans = [I2cTrans::write_read(
+                DEV_ADDR,
+                vec![Register::ID],
+                vec![$id],
+            )];
+            let mut sensor = new(&trans);
+            assert_eq!(None, sensor.device_variant());
+            assert_eq!(DeviceVariant::$variant, sensor.probe().unwrap());
+            assert_eq!(Some(DeviceVariant::$variant), sensor.device_variant());
+            destroy(sensor);
+        }
+    };
+}
+probe_test!(can_probe_genuine, 0xAB, Genuine);
+probe_test!(can_probe_clone_a8, 0xA8, CloneA8);
+probe_test!(can_probe_clone_9c, 0x9C, Clone9C);
+
+#[test]
+fn cannot_probe_wrong_id() {
+    let trans = [I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::ID],
+        vec![0x12],
+    )];
+    let mut sensor = new(&trans);
+    match sensor.probe() {
+        Err(Error::WrongDeviceId(0x12)) => (),
+        _ => panic!("WrongDeviceId error not returned."),
+    }
+    assert_eq!(None, sensor.device_variant());
+    destroy(sensor);
+}
+
+fn reset_trans() -> Vec<I2cTrans> {
+    vec![
+        I2cTrans::write(DEV_ADDR, vec![Register::ENABLE, 0]),
+        I2cTrans::write(DEV_ADDR, vec![Register::ATIME, 0xFF]),
+        I2cTrans::write(DEV_ADDR, vec![Register::WTIME, 0xFF, 0, 0, 0, 0]),
+        I2cTrans::write(DEV_ADDR, vec![Register::PILT, 0]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![
+                Register::PIHT,
+                0,
+                0,
+                DEFAULT_CONFIG1,
+                DEFAULT_PPULSE,
+                0,
+                DEFAULT_CONFIG2,
+            ],
+        ),
+        I2cTrans::write(DEV_ADDR, vec![Register::POFFSET_UR, 0, 0, 0, 0, 0, 0, 0]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GOFFSET_U, 0, 0, DEFAULT_GPULSE, 0]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::GOFFSET_R, 0, 0, BitFlags::GFIFO_CLR],
+        ),
+        I2cTrans::write(DEV_ADDR, vec![Register::AICLEAR]),
+    ]
+}
+
+#[test]
+fn can_reset_to_defaults() {
+    let mut sensor = new(&reset_trans());
+    sensor.reset_to_defaults().unwrap();
+    destroy(sensor);
+}
+
+#[test]
+fn reset_restores_cached_defaults() {
+    let mut trans = vec![
+        I2cTrans::write(DEV_ADDR, vec![Register::ENABLE, BitFlags::PON]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, BitFlags::AGAIN1]),
+        I2cTrans::write(DEV_ADDR, vec![Register::ATIME, 0]),
+    ];
+    trans.extend(reset_trans());
+    trans.extend_from_slice(&[
+        I2cTrans::write(DEV_ADDR, vec![Register::ENABLE, BitFlags::AEN]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, BitFlags::PGAIN0]),
+    ]);
+    let mut sensor = new(&trans);
+    sensor.enable().unwrap();
+    sensor.set_light_gain(LightGain::X16).unwrap();
+    sensor.set_light_integration_time(0).unwrap();
+    sensor.reset_to_defaults().unwrap();
+    sensor.enable_light().unwrap();
+    sensor.set_proximity_gain(ProximityGain::X2).unwrap();
+    assert_eq!(1025, sensor.max_light_count());
+    destroy(sensor);
+}
+
+fn sync_trans() -> Vec<I2cTrans> {
+    vec![
+        I2cTrans::write_read(DEV_ADDR, vec![Register::ENABLE], vec![BitFlags::PON, 0xF6]),
+        I2cTrans::write_read(
+            DEV_ADDR,
+            vec![Register::PERS],
+            vec![0x12, DEFAULT_CONFIG1 | BitFlags::WLONG, 0x89, 0x34, 0xD6],
+        ),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::CONFIG3], vec![0x07]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::GCONFIG1], vec![0x78, 0x9A]),
+        I2cTrans::write_read(DEV_ADDR, vec![Register::GPULSE], vec![0xBC]),
+        I2cTrans::write_read(
+            DEV_ADDR,
+            vec![Register::GCONFIG3],
+            vec![0x01, BitFlags::GIEN | BitFlags::GFIFO_CLR],
+        ),
+    ]
+}
+
+#[test]
+fn can_sync_from_device() {
+    let mut sensor = new(&sync_trans());
+    sensor.sync_from_device().unwrap();
+    assert_eq!(10 * 1025, sensor.max_light_count());
+    destroy(sensor);
+}
+
+#[test]
+fn synced_settings_are_kept() {
+    let mut trans = sync_trans();
+    trans.extend_from_slice(&[
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::ENABLE, BitFlags::PON | BitFlags::AEN],
+        ),
+        I2cTrans::write(DEV_ADDR, vec![Register::PERS, 0x10]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONFIG1, DEFAULT_CONFIG1]),
+        I2cTrans::write(DEV_ADDR, vec![Register::PPULSE, 0x80]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, 0x37]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONFIG2, 0x56]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONFIG3, 0x17]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG1, 0x7B]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG2, 0x9F]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GPULSE, 0xBF]),
+        I2cTrans::write(DEV_ADDR, vec![Register::GCONFIG3, 0x00]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::GCONFIG4, BitFlags::GIEN | BitFlags::GMODE],
+        ),
+    ]);
+    let mut sensor = new(&trans);
+    sensor.sync_from_device().unwrap();
+    sensor.enable_light().unwrap();
+    sensor.set_light_interrupt_persistence(0).unwrap();
+    sensor.disable_wait_long().unwrap();
+    sensor.set_proximity_pulse_count(1).unwrap();
+    sensor.set_light_gain(LightGain::X64).unwrap();
+    sensor.disable_proximity_saturation_interrupts().unwrap();
+    sensor.enable_sleep_after_interrupt().unwrap();
+    sensor
+        .set_gesture_exit_persistence(GestureExitPersistence::Seventh)
+        .unwrap();
+    sensor
+        .set_gesture_wait_time(GestureWaitTime::Ms39_2)
+        .unwrap();
+    sensor.set_gesture_pulse_count(64).unwrap();
+    sensor
+        .set_gesture_dimensions(GestureDimensions::All)
+        .unwrap();
+    sensor.enable_gesture_mode().unwrap();
+    destroy(sensor);
+}
+
+macro_rules! read_status_test {
+    ($name:ident, $value:expr, $($field:ident),*) => {
+        read_test!(
+            $name,
+            read_status,
+            DeviceStatus {
+                $($field: true,)*
+                ..DeviceStatus::default()
+            },
+            STATUS,
+            $value
+        );
+    };
+}
+read_status_test!(read_status_empty, 0,);
+read_status_test!(read_status_avalid, BitFlags::AVALID, light_data_valid);
+read_status_test!(read_status_pvalid, BitFlags::PVALID, proximity_data_valid);
+read_status_test!(read_status_gint, BitFlags::GINT, gesture_interrupt);
+read_status_test!(read_status_aint, BitFlags::AINT, light_interrupt);
+read_status_test!(read_status_pint, BitFlags::PINT, proximity_interrupt);
+read_status_test!(
+    read_status_pgsat,
+    BitFlags::PGSAT,
+    proximity_gesture_saturation
+);
+read_status_test!(
+    read_status_cpsat,
+    BitFlags::CPSAT,
+    clear_photodiode_saturation
+);
+read_test!(
+    read_status_all,
+    read_status,
+    DeviceStatus {
+        light_data_valid: true,
+        proximity_data_valid: true,
+        gesture_interrupt: true,
+        light_interrupt: true,
+        proximity_interrupt: true,
+        proximity_gesture_saturation: true,
+        clear_photodiode_saturation: true,
+    },
+    STATUS,
+    0xFF
+);
+
 write_test!(can_enable_wait, enable_wait, ENABLE, BitFlags::WEN);
 write_test!(can_disable_wait, disable_wait, ENABLE, 0);
 write_test!(
@@ -26,5 +246,100 @@ write_test!(
 write_test!(dis_wlong, disable_wait_long, CONFIG1, DEFAULT_CONFIG1);
 write_test!(set_wtime, set_wait_time, WTIME, 0x0F, 0x0F);
 
+write_test!(set_wait_cycles_1, set_wait_cycles, WTIME, 0xFF, 1);
+write_test!(set_wait_cycles_256, set_wait_cycles, WTIME, 0, 256);
+
+#[test]
+fn cannot_set_invalid_wait_cycles() {
+    let mut sensor = new(&[]);
+    for cycles in [0, 257] {
+        match sensor.set_wait_cycles(cycles) {
+            Err(Error::InvalidInputData) => (),
+            _ => panic!("InvalidInputData error not returned."),
+        }
+    }
+    destroy(sensor);
+}
+
+macro_rules! set_wait_duration_test {
+    ($name:ident, $us:expr, $wtime:expr, $config1:expr, $achieved_us:expr) => {
+        #[test]
+        fn $name() {
+            let trans = [
+                I2cTrans::write(DEV_ADDR, vec![Register::CONFIG1, $config1]),
+                I2cTrans::write(DEV_ADDR, vec![Register::WTIME, $wtime]),
+            ];
+            let mut sensor = new(&trans);
+            let achieved = sensor
+                .set_wait_duration(Duration::from_micros($us))
+                .unwrap();
+            assert_eq!(Duration::from_micros($achieved_us), achieved);
+            destroy(sensor);
+        }
+    };
+}
+set_wait_duration_test!(set_wait_0, 0, 0xFF, DEFAULT_CONFIG1, 2780);
+set_wait_duration_test!(set_wait_round_down, 4000, 0xFF, DEFAULT_CONFIG1, 2780);
+set_wait_duration_test!(set_wait_round_up, 4200, 0xFE, DEFAULT_CONFIG1, 5560);
+set_wait_duration_test!(set_wait_max_short, 711_680, 0, DEFAULT_CONFIG1, 711_680);
+set_wait_duration_test!(set_wait_712ms, 712_000, 0, DEFAULT_CONFIG1, 711_680);
+// 256 short cycles are closer than 21 long cycles (700.56ms).
+set_wait_duration_test!(set_wait_nearest_short, 713_400, 0, DEFAULT_CONFIG1, 711_680);
+// 22 long cycles (733.92ms) are closer than 256 short cycles.
+set_wait_duration_test!(
+    set_wait_min_long,
+    723_000,
+    234,
+    DEFAULT_CONFIG1 | BitFlags::WLONG,
+    22 * 33_360
+);
+set_wait_duration_test!(
+    set_wait_max_long,
+    8_540_160,
+    0,
+    DEFAULT_CONFIG1 | BitFlags::WLONG,
+    8_540_160
+);
+
+#[test]
+fn wait_long_is_disabled_for_short_duration() {
+    let trans = [
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::CONFIG1, DEFAULT_CONFIG1 | BitFlags::WLONG],
+        ),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONFIG1, DEFAULT_CONFIG1]),
+        I2cTrans::write(DEV_ADDR, vec![Register::WTIME, 0xF6]),
+    ];
+    let mut sensor = new(&trans);
+    sensor.enable_wait_long().unwrap();
+    sensor.set_wait_duration(Duration::from_millis(28)).unwrap();
+    destroy(sensor);
+}
+
+#[test]
+fn cannot_set_too_long_wait_duration() {
+    let mut sensor = new(&[]);
+    match sensor.set_wait_duration(Duration::from_secs(9)) {
+        Err(Error::InvalidInputData) => (),
+        _ => panic!("InvalidInputData error not returned."),
+    }
+    destroy(sensor);
+}
+
+write_test!(en_sai, enable_sleep_after_interrupt, CONFIG3, BitFlags::SAI);
+write_test!(dis_sai, disable_sleep_after_interrupt, CONFIG3, 0);
+
 empty_write_test!(force_int, force_interrupt, IFORCE);
 empty_write_test!(clear_ints, clear_interrupts, AICLEAR);
+
+#[test]
+fn i2c_error_kind_is_forwarded() {
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+    let kind = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+    let trans = [I2cTrans::write(DEV_ADDR, vec![Register::ENABLE, BitFlags::PON]).with_error(kind)];
+    let mut sensor = new(&trans);
+    let error = sensor.enable().unwrap_err();
+    assert_eq!(Some(kind), error.i2c_error_kind());
+    destroy(sensor);
+}
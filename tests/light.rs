@@ -1,7 +1,8 @@
-extern crate apds9960;
-use apds9960::LightData;
-extern crate embedded_hal_mock as hal;
-use hal::i2c::Transaction as I2cTrans;
+use apds9960::{
+    Error, IrCompensatedLightData, LightCoefficients, LightData, LightGain, LightSettings,
+};
+use core::time::Duration;
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTrans;
 mod common;
 use common::{destroy, new, BitFlags, Register, DEFAULT_CONFIG2, DEV_ADDR};
 
@@ -22,8 +23,115 @@ write_test!(
     DEFAULT_CONFIG2
 );
 write_test!(set_atime, set_light_integration_time, ATIME, 0x0F, 0x0F);
+write_test!(
+    set_atime_cycles_1,
+    set_light_integration_cycles,
+    ATIME,
+    0xFF,
+    1
+);
+write_test!(
+    set_atime_cycles_256,
+    set_light_integration_cycles,
+    ATIME,
+    0,
+    256
+);
+
+#[test]
+fn cannot_set_invalid_atime_cycles() {
+    let mut sensor = new(&[]);
+    for cycles in [0, 257] {
+        match sensor.set_light_integration_cycles(cycles) {
+            Err(Error::InvalidInputData) => (),
+            _ => panic!("InvalidInputData error not returned."),
+        }
+    }
+    destroy(sensor);
+}
+
+macro_rules! set_atime_duration_test {
+    ($name:ident, $us:expr, $atime:expr, $achieved_us:expr, $max_count:expr) => {
+        #[test]
+        fn $name() {
+            let trans = [I2cTrans::write(DEV_ADDR, vec![Register::ATIME, $atime])];
+            let mut sensor = new(&trans);
+            let achieved = sensor
+                .set_light_integration_duration(Duration::from_micros($us))
+                .unwrap();
+            assert_eq!(Duration::from_micros($achieved_us), achieved);
+            assert_eq!($max_count, sensor.max_light_count());
+            destroy(sensor);
+        }
+    };
+}
+set_atime_duration_test!(set_atime_0us, 0, 0xFF, 2780, 1025);
+set_atime_duration_test!(set_atime_100ms, 100_000, 220, 36 * 2780, 36 * 1025);
+set_atime_duration_test!(set_atime_max_count, 178_000, 192, 64 * 2780, 65535);
+set_atime_duration_test!(set_atime_max, 711_680, 0, 711_680, 65535);
+
+#[test]
+fn cannot_set_too_long_atime_duration() {
+    let mut sensor = new(&[]);
+    match sensor.set_light_integration_duration(Duration::from_millis(714)) {
+        Err(Error::InvalidInputData) => (),
+        _ => panic!("InvalidInputData error not returned."),
+    }
+    destroy(sensor);
+}
+
+#[test]
+fn max_light_count_default() {
+    let sensor = new(&[]);
+    assert_eq!(1025, sensor.max_light_count());
+    destroy(sensor);
+}
 empty_write_test!(clear_int, clear_light_interrupt, CICLEAR);
 
+macro_rules! set_again_test {
+    ($name:ident, $variant:ident, $value:expr) => {
+        write_test!($name, set_light_gain, CONTROL, $value, LightGain::$variant);
+    };
+}
+set_again_test!(set_again_1, X1, 0);
+set_again_test!(set_again_4, X4, BitFlags::AGAIN0);
+set_again_test!(set_again_16, X16, BitFlags::AGAIN1);
+set_again_test!(set_again_64, X64, BitFlags::AGAIN1 | BitFlags::AGAIN0);
+
+write_test!(set_apers_0, set_light_interrupt_persistence, PERS, 0, 0);
+write_test!(set_apers_3, set_light_interrupt_persistence, PERS, 3, 3);
+write_test!(set_apers_5, set_light_interrupt_persistence, PERS, 4, 5);
+write_test!(set_apers_60, set_light_interrupt_persistence, PERS, 15, 60);
+
+macro_rules! invalid_apers_test {
+    ($name:ident, $cycles:expr) => {
+        #[test]
+        fn $name() {
+            let mut sensor = new(&[]);
+            match sensor.set_light_interrupt_persistence($cycles) {
+                Err(Error::InvalidInputData) => (),
+                _ => panic!("InvalidInputData error not returned."),
+            }
+            destroy(sensor);
+        }
+    };
+}
+invalid_apers_test!(cannot_set_apers_4, 4);
+invalid_apers_test!(cannot_set_apers_11, 11);
+invalid_apers_test!(cannot_set_apers_65, 65);
+
+#[test]
+fn persistence_settings_are_kept() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::PERS, 0x04]),
+        I2cTrans::write(DEV_ADDR, vec![Register::PERS, 0x74]),
+    ];
+    let mut sensor = new(&trans);
+    sensor.set_light_interrupt_persistence(5).unwrap();
+    sensor.set_proximity_interrupt_persistence(7).unwrap();
+    destroy(sensor);
+}
+
 #[test]
 fn set_low_threshold() {
     let trans = [I2cTrans::write(DEV_ADDR, vec![Register::AILTL, 0xCD, 0xAB])];
@@ -141,3 +249,306 @@ fn cannot_read_light_if_not_valid() {
     assert_would_block!(sensor.read_light());
     destroy(sensor);
 }
+
+#[test]
+fn light_settings_default() {
+    let sensor = new(&[]);
+    let expected = LightSettings {
+        integration_cycles: 1,
+        gain: LightGain::X1,
+    };
+    assert_eq!(expected, sensor.light_settings());
+    destroy(sensor);
+}
+
+#[test]
+fn can_set_light_settings() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::ATIME, 220]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, BitFlags::AGAIN0]),
+    ];
+    let mut sensor = new(&trans);
+    let settings = LightSettings {
+        integration_cycles: 36,
+        gain: LightGain::X4,
+    };
+    sensor.set_light_settings(settings).unwrap();
+    assert_eq!(settings, sensor.light_settings());
+    destroy(sensor);
+}
+
+#[test]
+fn light_settings_are_kept() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::ATIME, 220]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, BitFlags::AGAIN1]),
+    ];
+    let mut sensor = new(&trans);
+    sensor.set_light_integration_time(220).unwrap();
+    sensor.set_light_gain(LightGain::X16).unwrap();
+    let expected = LightSettings {
+        integration_cycles: 36,
+        gain: LightGain::X16,
+    };
+    assert_eq!(expected, sensor.light_settings());
+    destroy(sensor);
+}
+
+macro_rules! millilux_test {
+    ($name:ident, [$c:expr, $r:expr, $g:expr, $b:expr], $cycles:expr, $gain:ident, $dgf:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let data = LightData {
+                clear: $c,
+                red: $r,
+                green: $g,
+                blue: $b,
+            };
+            let settings = LightSettings {
+                integration_cycles: $cycles,
+                gain: LightGain::$gain,
+            };
+            let coefficients = LightCoefficients {
+                dgf: $dgf,
+                ..LightCoefficients::default()
+            };
+            assert_eq!($expected, data.millilux(&settings, &coefficients));
+        }
+    };
+}
+// Expected values computed with the DN40 formula in floating point and truncated.
+millilux_test!(millilux_dark, [0, 0, 0, 0], 1, X1, 310, Some(0));
+millilux_test!(
+    millilux_indoor,
+    [1000, 400, 450, 300],
+    36,
+    X4,
+    310,
+    Some(247_259)
+);
+millilux_test!(
+    millilux_indoor_cover_glass,
+    [1000, 400, 450, 300],
+    36,
+    X4,
+    620,
+    Some(494_519)
+);
+millilux_test!(
+    millilux_bright,
+    [60000, 20000, 22000, 15000],
+    64,
+    X1,
+    310,
+    Some(31_466_951)
+);
+millilux_test!(
+    millilux_max_dgf,
+    [65534, 0, 65534, 0],
+    256,
+    X1,
+    u32::MAX,
+    Some(u32::MAX)
+);
+millilux_test!(millilux_negative, [100, 20, 20, 60], 10, X16, 310, Some(0));
+millilux_test!(millilux_saturated, [1025, 0, 0, 0], 1, X1, 310, None);
+millilux_test!(
+    millilux_saturated_65535,
+    [65535, 0, 0, 0],
+    256,
+    X1,
+    310,
+    None
+);
+
+macro_rules! cct_test {
+    ($name:ident, $method:ident, [$c:expr, $r:expr, $g:expr, $b:expr], $cycles:expr, $expected:expr $(, $coefficients:expr)?) => {
+        #[test]
+        fn $name() {
+            let data = LightData {
+                clear: $c,
+                red: $r,
+                green: $g,
+                blue: $b,
+            };
+            let settings = LightSettings {
+                integration_cycles: $cycles,
+                gain: LightGain::X4,
+            };
+            assert_eq!($expected, data.$method(&settings $(, &$coefficients)?));
+        }
+    };
+}
+// Expected values agree with the formulas computed in floating point within 2K.
+cct_test!(
+    cct_neutral,
+    cct,
+    [1000, 400, 450, 300],
+    36,
+    Some(4028),
+    LightCoefficients::default()
+);
+cct_test!(
+    cct_warm,
+    cct,
+    [1000, 500, 400, 200],
+    36,
+    Some(2661),
+    LightCoefficients::default()
+);
+cct_test!(
+    cct_calibrated,
+    cct,
+    [1000, 400, 450, 300],
+    36,
+    Some(4469),
+    LightCoefficients {
+        ct_coef: 4000,
+        ct_offset: 1700,
+        ..LightCoefficients::default()
+    }
+);
+cct_test!(
+    cct_no_red,
+    cct,
+    [1000, 0, 450, 300],
+    36,
+    None,
+    LightCoefficients::default()
+);
+cct_test!(
+    cct_saturated,
+    cct,
+    [1025, 400, 450, 300],
+    1,
+    None,
+    LightCoefficients::default()
+);
+cct_test!(
+    mccamy_neutral,
+    cct_mccamy,
+    [1000, 400, 450, 300],
+    36,
+    Some(4329)
+);
+cct_test!(
+    mccamy_warm,
+    cct_mccamy,
+    [1000, 500, 400, 200],
+    36,
+    Some(2669)
+);
+cct_test!(
+    mccamy_cold,
+    cct_mccamy,
+    [1000, 300, 400, 400],
+    36,
+    Some(10984)
+);
+cct_test!(mccamy_invalid, cct_mccamy, [100, 20, 20, 60], 36, None);
+cct_test!(mccamy_saturated, cct_mccamy, [1025, 400, 450, 300], 1, None);
+
+macro_rules! ir_test {
+    ($name:ident, [$c:expr, $r:expr, $g:expr, $b:expr], $expected:expr) => {
+        #[test]
+        fn $name() {
+            let data = LightData {
+                clear: $c,
+                red: $r,
+                green: $g,
+                blue: $b,
+            };
+            assert_eq!($expected, data.ir());
+        }
+    };
+}
+ir_test!(ir_none, [1000, 300, 300, 300], 0);
+ir_test!(ir_some, [1000, 400, 450, 300], 75);
+ir_test!(ir_odd, [1000, 400, 450, 301], 75);
+ir_test!(ir_max, [0, 0xFFFF, 0xFFFF, 0xFFFF], 0xFFFF);
+
+#[test]
+fn ir_compensated() {
+    let data = LightData {
+        clear: 1000,
+        red: 400,
+        green: 450,
+        blue: 50,
+    };
+    let expected = IrCompensatedLightData {
+        clear: 1000,
+        red: 400,
+        green: 450,
+        blue: 50,
+        ir: 0,
+    };
+    assert_eq!(expected, data.ir_compensated());
+    let data = LightData {
+        clear: 1000,
+        red: 400,
+        green: 450,
+        blue: 300,
+    };
+    let expected = IrCompensatedLightData {
+        clear: 925,
+        red: 325,
+        green: 375,
+        blue: 225,
+        ir: 75,
+    };
+    assert_eq!(expected, data.ir_compensated());
+    assert_eq!(expected, IrCompensatedLightData::from(data));
+}
+
+#[test]
+fn ir_compensated_can_be_negative() {
+    let data = LightData {
+        clear: 100,
+        red: 20,
+        green: 20,
+        blue: 200,
+    };
+    let compensated = data.ir_compensated();
+    assert_eq!(70, compensated.ir);
+    assert_eq!(-50, compensated.red);
+    assert_eq!(-50, compensated.green);
+}
+
+#[test]
+fn ir_compensated_lux_and_cct() {
+    let data = LightData {
+        clear: 1000,
+        red: 400,
+        green: 450,
+        blue: 300,
+    };
+    let settings = LightSettings {
+        integration_cycles: 36,
+        gain: LightGain::X4,
+    };
+    let coefficients = LightCoefficients::default();
+    let compensated = data.ir_compensated();
+    assert_eq!(
+        Some(247_259),
+        compensated.millilux(&settings, &coefficients)
+    );
+    assert_eq!(Some(4028), compensated.cct(&settings, &coefficients));
+}
+
+#[test]
+fn ir_compensated_saturation() {
+    let data = LightData {
+        clear: 1025,
+        red: 600,
+        green: 600,
+        blue: 600,
+    };
+    let settings = LightSettings {
+        integration_cycles: 1,
+        gain: LightGain::X1,
+    };
+    let compensated = data.ir_compensated();
+    let coefficients = LightCoefficients::default();
+    assert_eq!(None, compensated.millilux(&settings, &coefficients));
+    assert_eq!(None, compensated.cct(&settings, &coefficients));
+}
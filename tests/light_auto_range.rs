@@ -0,0 +1,182 @@
+use apds9960::{Apds9960, LightAutoRange, LightData, LightGain, LightSample, LightSettings};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTrans};
+mod common;
+use common::{destroy, new, BitFlags, Register, DEV_ADDR};
+
+fn apply(atime: u8, control: u8) -> [I2cTrans; 2] {
+    [
+        I2cTrans::write(DEV_ADDR, vec![Register::ATIME, atime]),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, control]),
+    ]
+}
+
+fn read(status: u8, clear: u16) -> [I2cTrans; 2] {
+    let [low, high] = clear.to_le_bytes();
+    [
+        I2cTrans::write_read(DEV_ADDR, vec![Register::STATUS], vec![status]),
+        I2cTrans::write_read(
+            DEV_ADDR,
+            vec![Register::CDATAL],
+            vec![low, high, 1, 0, 2, 0, 3, 0],
+        ),
+    ]
+}
+
+fn data(clear: u16) -> LightData {
+    LightData {
+        clear,
+        red: 1,
+        green: 2,
+        blue: 3,
+    }
+}
+
+const INITIAL_SETTINGS: LightSettings = LightSettings {
+    integration_cycles: 64,
+    gain: LightGain::X4,
+};
+
+/// Configure the initial settings and discard the first data.
+fn configure() -> Vec<I2cTrans> {
+    let mut trans = Vec::new();
+    trans.extend(apply(192, BitFlags::AGAIN0));
+    trans.extend(read(BitFlags::AVALID, 0));
+    trans
+}
+
+fn start(auto_range: &mut LightAutoRange, sensor: &mut Apds9960<I2cMock>) {
+    assert_would_block!(auto_range.read_light(sensor));
+    assert_would_block!(auto_range.read_light(sensor));
+}
+
+#[test]
+fn initial_settings() {
+    let auto_range = LightAutoRange::new();
+    assert_eq!(INITIAL_SETTINGS, auto_range.settings());
+}
+
+#[test]
+fn configures_and_discards_first_data() {
+    let mut sensor = new(&configure());
+    let mut auto_range = LightAutoRange::default();
+    start(&mut auto_range, &mut sensor);
+    destroy(sensor);
+}
+
+#[test]
+fn would_block_if_not_valid() {
+    let mut trans = configure();
+    trans.push(I2cTrans::write_read(
+        DEV_ADDR,
+        vec![Register::STATUS],
+        vec![0],
+    ));
+    let mut sensor = new(&trans);
+    let mut auto_range = LightAutoRange::new();
+    start(&mut auto_range, &mut sensor);
+    assert_would_block!(auto_range.read_light(&mut sensor));
+    destroy(sensor);
+}
+
+#[test]
+fn keeps_settings_in_range() {
+    let mut trans = configure();
+    trans.extend(read(BitFlags::AVALID, 30000));
+    let mut sensor = new(&trans);
+    let mut auto_range = LightAutoRange::new();
+    start(&mut auto_range, &mut sensor);
+    let expected = LightSample {
+        data: data(30000),
+        settings: INITIAL_SETTINGS,
+        saturated: false,
+    };
+    assert_eq!(expected, auto_range.read_light(&mut sensor).unwrap());
+    assert_eq!(INITIAL_SETTINGS, auto_range.settings());
+    destroy(sensor);
+}
+
+#[test]
+fn reduces_sensitivity_on_saturation() {
+    let mut trans = configure();
+    trans.extend(read(BitFlags::AVALID | BitFlags::CPSAT, 65535));
+    trans.push(I2cTrans::write(DEV_ADDR, vec![Register::CICLEAR]));
+    trans.extend(apply(192, 0));
+    // data integrated partially with the previous settings is discarded
+    trans.extend(read(BitFlags::AVALID, 20000));
+    trans.extend(read(BitFlags::AVALID, 16000));
+    let mut sensor = new(&trans);
+    let mut auto_range = LightAutoRange::new();
+    start(&mut auto_range, &mut sensor);
+    let expected = LightSample {
+        data: data(65535),
+        settings: INITIAL_SETTINGS,
+        saturated: true,
+    };
+    assert_eq!(expected, auto_range.read_light(&mut sensor).unwrap());
+    assert_would_block!(auto_range.read_light(&mut sensor));
+    let settings = LightSettings {
+        integration_cycles: 64,
+        gain: LightGain::X1,
+    };
+    let expected = LightSample {
+        data: data(16000),
+        settings,
+        saturated: false,
+    };
+    assert_eq!(expected, auto_range.read_light(&mut sensor).unwrap());
+    destroy(sensor);
+}
+
+#[test]
+fn reduces_sensitivity_close_to_max_count() {
+    let mut trans = configure();
+    trans.extend(read(BitFlags::AVALID, 60000));
+    trans.extend(apply(192, 0));
+    let mut sensor = new(&trans);
+    let mut auto_range = LightAutoRange::new();
+    start(&mut auto_range, &mut sensor);
+    let sample = auto_range.read_light(&mut sensor).unwrap();
+    assert!(!sample.saturated);
+    assert_eq!(INITIAL_SETTINGS, sample.settings);
+    destroy(sensor);
+}
+
+#[test]
+fn increases_sensitivity_on_underflow() {
+    let mut trans = configure();
+    trans.extend(read(BitFlags::AVALID, 1000));
+    trans.extend(apply(192, BitFlags::AGAIN1));
+    let mut sensor = new(&trans);
+    let mut auto_range = LightAutoRange::new();
+    start(&mut auto_range, &mut sensor);
+    let sample = auto_range.read_light(&mut sensor).unwrap();
+    assert_eq!(INITIAL_SETTINGS, sample.settings);
+    let expected = LightSettings {
+        integration_cycles: 64,
+        gain: LightGain::X16,
+    };
+    assert_eq!(expected, auto_range.settings());
+    destroy(sensor);
+}
+
+#[test]
+fn reduces_integration_time_at_min_gain() {
+    let mut trans = configure();
+    trans.extend(read(BitFlags::AVALID, 65535));
+    trans.extend(apply(192, 0));
+    trans.extend(read(BitFlags::AVALID, 0));
+    trans.extend(read(BitFlags::AVALID, 65535));
+    trans.extend(apply(240, 0));
+    let mut sensor = new(&trans);
+    let mut auto_range = LightAutoRange::new();
+    start(&mut auto_range, &mut sensor);
+    auto_range.read_light(&mut sensor).unwrap();
+    assert_would_block!(auto_range.read_light(&mut sensor));
+    auto_range.read_light(&mut sensor).unwrap();
+    let expected = LightSettings {
+        integration_cycles: 16,
+        gain: LightGain::X1,
+    };
+    assert_eq!(expected, auto_range.settings());
+    destroy(sensor);
+}
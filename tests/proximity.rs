@@ -1,8 +1,9 @@
-extern crate apds9960;
-extern crate embedded_hal_mock as hal;
-use hal::i2c::Transaction as I2cTrans;
+use apds9960::{
+    Error, LedBoost, LedDrive, ProximityCalibration, ProximityGain, ProximityPulseLength,
+};
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTrans;
 mod common;
-use common::{destroy, new, BitFlags, Register, DEFAULT_CONFIG2, DEV_ADDR};
+use common::{destroy, new, BitFlags, Register, DEFAULT_CONFIG2, DEFAULT_PPULSE, DEV_ADDR};
 
 write_test!(can_enable, enable_proximity, ENABLE, BitFlags::PEN);
 write_test!(can_disable, disable_proximity, ENABLE, 0);
@@ -21,9 +22,228 @@ write_test!(
     DEFAULT_CONFIG2
 );
 
+macro_rules! set_led_drive_test {
+    ($name:ident, $variant:ident, $value:expr) => {
+        write_test!($name, set_led_drive, CONTROL, $value, LedDrive::$variant);
+    };
+}
+set_led_drive_test!(set_ldrive_100, Ma100, 0);
+set_led_drive_test!(set_ldrive_50, Ma50, BitFlags::LDRIVE0);
+set_led_drive_test!(set_ldrive_25, Ma25, BitFlags::LDRIVE1);
+set_led_drive_test!(
+    set_ldrive_12_5,
+    Ma12_5,
+    BitFlags::LDRIVE1 | BitFlags::LDRIVE0
+);
+
+macro_rules! set_led_boost_test {
+    ($name:ident, $variant:ident, $value:expr) => {
+        write_test!(
+            $name,
+            set_led_boost,
+            CONFIG2,
+            DEFAULT_CONFIG2 | $value,
+            LedBoost::$variant
+        );
+    };
+}
+set_led_boost_test!(set_led_boost_100, Percent100, 0);
+set_led_boost_test!(set_led_boost_150, Percent150, BitFlags::LED_BOOST0);
+set_led_boost_test!(set_led_boost_200, Percent200, BitFlags::LED_BOOST1);
+set_led_boost_test!(
+    set_led_boost_300,
+    Percent300,
+    BitFlags::LED_BOOST1 | BitFlags::LED_BOOST0
+);
+
+#[test]
+fn led_boost_is_kept() {
+    let trans = [
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::CONFIG2, DEFAULT_CONFIG2 | BitFlags::LED_BOOST1],
+        ),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![
+                Register::CONFIG2,
+                DEFAULT_CONFIG2 | BitFlags::LED_BOOST1 | BitFlags::PSIEN,
+            ],
+        ),
+    ];
+    let mut sensor = new(&trans);
+    sensor.set_led_boost(LedBoost::Percent200).unwrap();
+    sensor.enable_proximity_saturation_interrupts().unwrap();
+    destroy(sensor);
+}
+
+macro_rules! set_pgain_test {
+    ($name:ident, $variant:ident, $value:expr) => {
+        write_test!(
+            $name,
+            set_proximity_gain,
+            CONTROL,
+            $value,
+            ProximityGain::$variant
+        );
+    };
+}
+set_pgain_test!(set_pgain_1, X1, 0);
+set_pgain_test!(set_pgain_2, X2, BitFlags::PGAIN0);
+set_pgain_test!(set_pgain_4, X4, BitFlags::PGAIN1);
+set_pgain_test!(set_pgain_8, X8, BitFlags::PGAIN1 | BitFlags::PGAIN0);
+
+#[test]
+fn control_settings_are_kept() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::CONTROL, BitFlags::LDRIVE0]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::CONTROL, BitFlags::LDRIVE0 | BitFlags::PGAIN1],
+        ),
+    ];
+    let mut sensor = new(&trans);
+    sensor.set_led_drive(LedDrive::Ma50).unwrap();
+    sensor.set_proximity_gain(ProximityGain::X4).unwrap();
+    destroy(sensor);
+}
+
+macro_rules! set_pplen_test {
+    ($name:ident, $variant:ident, $value:expr) => {
+        write_test!(
+            $name,
+            set_proximity_pulse_length,
+            PPULSE,
+            $value,
+            ProximityPulseLength::$variant
+        );
+    };
+}
+set_pplen_test!(set_pplen_4, Us4, 0);
+set_pplen_test!(set_pplen_8, Us8, BitFlags::PPLEN0);
+set_pplen_test!(set_pplen_16, Us16, BitFlags::PPLEN1);
+set_pplen_test!(set_pplen_32, Us32, BitFlags::PPLEN1 | BitFlags::PPLEN0);
+
+write_test!(
+    set_ppulse_1,
+    set_proximity_pulse_count,
+    PPULSE,
+    DEFAULT_PPULSE,
+    1
+);
+write_test!(
+    set_ppulse_64,
+    set_proximity_pulse_count,
+    PPULSE,
+    DEFAULT_PPULSE | 63,
+    64
+);
+
+macro_rules! invalid_ppulse_test {
+    ($name:ident, $count:expr) => {
+        #[test]
+        fn $name() {
+            let mut sensor = new(&[]);
+            match sensor.set_proximity_pulse_count($count) {
+                Err(Error::InvalidInputData) => (),
+                _ => panic!("InvalidInputData error not returned."),
+            }
+            destroy(sensor);
+        }
+    };
+}
+invalid_ppulse_test!(cannot_set_ppulse_0, 0);
+invalid_ppulse_test!(cannot_set_ppulse_65, 65);
+
+#[test]
+fn ppulse_settings_are_kept() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::PPULSE, BitFlags::PPLEN1]),
+        I2cTrans::write(DEV_ADDR, vec![Register::PPULSE, BitFlags::PPLEN1 | 9]),
+    ];
+    let mut sensor = new(&trans);
+    sensor
+        .set_proximity_pulse_length(ProximityPulseLength::Us16)
+        .unwrap();
+    sensor.set_proximity_pulse_count(10).unwrap();
+    destroy(sensor);
+}
+
 write_test!(set_low_th, set_proximity_low_threshold, PILT, 0xAB, 0xAB);
 write_test!(set_high_th, set_proximity_high_threshold, PIHT, 0xAB, 0xAB);
 
+write_test!(set_ppers_0, set_proximity_interrupt_persistence, PERS, 0, 0);
+write_test!(
+    set_ppers_15,
+    set_proximity_interrupt_persistence,
+    PERS,
+    0xF0,
+    15
+);
+
+#[test]
+fn cannot_set_ppers_16() {
+    let mut sensor = new(&[]);
+    match sensor.set_proximity_interrupt_persistence(16) {
+        Err(Error::InvalidInputData) => (),
+        _ => panic!("InvalidInputData error not returned."),
+    }
+    destroy(sensor);
+}
+
+write_test!(
+    en_pcmp,
+    enable_proximity_gain_compensation,
+    CONFIG3,
+    BitFlags::PCMP
+);
+write_test!(dis_pcmp, disable_proximity_gain_compensation, CONFIG3, 0);
+write_test!(
+    en_pmask_u,
+    enable_proximity_up_mask,
+    CONFIG3,
+    BitFlags::PMASK_U
+);
+write_test!(dis_pmask_u, disable_proximity_up_mask, CONFIG3, 0);
+write_test!(
+    en_pmask_d,
+    enable_proximity_down_mask,
+    CONFIG3,
+    BitFlags::PMASK_D
+);
+write_test!(dis_pmask_d, disable_proximity_down_mask, CONFIG3, 0);
+write_test!(
+    en_pmask_l,
+    enable_proximity_left_mask,
+    CONFIG3,
+    BitFlags::PMASK_L
+);
+write_test!(dis_pmask_l, disable_proximity_left_mask, CONFIG3, 0);
+write_test!(
+    en_pmask_r,
+    enable_proximity_right_mask,
+    CONFIG3,
+    BitFlags::PMASK_R
+);
+write_test!(dis_pmask_r, disable_proximity_right_mask, CONFIG3, 0);
+
+#[test]
+fn config3_settings_are_kept() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![Register::CONFIG3, BitFlags::PMASK_U]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![Register::CONFIG3, BitFlags::PMASK_U | BitFlags::PCMP],
+        ),
+        I2cTrans::write(DEV_ADDR, vec![Register::CONFIG3, BitFlags::PCMP]),
+    ];
+    let mut sensor = new(&trans);
+    sensor.enable_proximity_up_mask().unwrap();
+    sensor.enable_proximity_gain_compensation().unwrap();
+    sensor.disable_proximity_up_mask().unwrap();
+    destroy(sensor);
+}
+
 write_test!(
     set_ur_off,
     set_proximity_up_right_offset,
@@ -41,13 +261,43 @@ write_test!(
 
 #[test]
 fn can_set_poffsets() {
-    let data = vec![Register::POFFSET_UR, 55, i8::from(-56) as u8];
+    let data = vec![Register::POFFSET_UR, 55, 0x80 | 56];
     let trans = [I2cTrans::write(DEV_ADDR, data)];
     let mut sensor = new(&trans);
     sensor.set_proximity_offsets(55, -56).unwrap();
     destroy(sensor);
 }
 
+macro_rules! invalid_poffset_test {
+    ($name:ident, $sensor:ident => $call:expr) => {
+        #[test]
+        fn $name() {
+            let mut $sensor = new(&[]);
+            match $call {
+                Err(Error::InvalidInputData) => (),
+                _ => panic!("InvalidInputData error not returned."),
+            }
+            destroy($sensor);
+        }
+    };
+}
+invalid_poffset_test!(cannot_set_ur_off_min, s => s.set_proximity_up_right_offset(-128));
+invalid_poffset_test!(cannot_set_dl_off_min, s => s.set_proximity_down_left_offset(-128));
+invalid_poffset_test!(cannot_set_poffsets_min, s => s.set_proximity_offsets(0, -128));
+
+#[test]
+fn can_set_proximity_calibration() {
+    let data = vec![Register::POFFSET_UR, 55, 0x80 | 56];
+    let trans = [I2cTrans::write(DEV_ADDR, data)];
+    let mut sensor = new(&trans);
+    let calibration = ProximityCalibration {
+        up_right_offset: 55,
+        down_left_offset: -56,
+    };
+    sensor.set_proximity_calibration(calibration).unwrap();
+    destroy(sensor);
+}
+
 empty_write_test!(clear_int, clear_proximity_interrupt, PICLEAR);
 
 read_test!(
@@ -0,0 +1,87 @@
+use apds9960::{Apds9960, Error, ProximityCalibration};
+mod common;
+use common::{BitFlags, FakeDevice, Register};
+
+fn new(crosstalk_up_right: i16, crosstalk_down_left: i16) -> Apds9960<FakeDevice> {
+    let mut device = FakeDevice::new();
+    device.proximity_crosstalk = [crosstalk_up_right, crosstalk_down_left];
+    Apds9960::new(device)
+}
+
+macro_rules! calibration_test {
+    ($name:ident, $crosstalk_ur:expr, $crosstalk_dl:expr, $expected_ur:expr, $expected_dl:expr) => {
+        #[test]
+        fn $name() {
+            let mut sensor = new($crosstalk_ur, $crosstalk_dl);
+            let expected = ProximityCalibration {
+                up_right_offset: $expected_ur,
+                down_left_offset: $expected_dl,
+            };
+            assert_eq!(expected, sensor.calibrate_proximity_offsets().unwrap());
+            let device = sensor.destroy();
+            let registers = device.registers;
+            assert_eq!(
+                $expected_ur as u8,
+                registers[usize::from(Register::POFFSET_UR)]
+            );
+            assert_eq!(
+                $expected_dl as u8,
+                registers[usize::from(Register::POFFSET_DL)]
+            );
+            assert_eq!(0, registers[usize::from(Register::CONFIG3)]);
+            assert_eq!(0, registers[usize::from(Register::ENABLE)]);
+        }
+    };
+}
+calibration_test!(no_crosstalk, 0, 0, 0, 0);
+calibration_test!(crosstalk, 50, 5, 50, 5);
+calibration_test!(crosstalk_one_pair, 0, 100, 0, 100);
+calibration_test!(crosstalk_max, 200, 128, 127, 127);
+
+#[test]
+fn masks_and_enable_are_restored() {
+    let mut sensor = new(20, 30);
+    sensor.enable().unwrap();
+    sensor.enable_light().unwrap();
+    sensor.enable_proximity_gain_compensation().unwrap();
+    sensor.enable_proximity_up_mask().unwrap();
+    sensor.calibrate_proximity_offsets().unwrap();
+    sensor.enable_proximity_left_mask().unwrap();
+    let registers = sensor.destroy().registers;
+    assert_eq!(
+        BitFlags::PCMP | BitFlags::PMASK_U | BitFlags::PMASK_L,
+        registers[usize::from(Register::CONFIG3)]
+    );
+    assert_eq!(
+        BitFlags::PON | BitFlags::AEN,
+        registers[usize::from(Register::ENABLE)]
+    );
+}
+
+#[test]
+fn times_out_and_restores_state() {
+    let mut device = FakeDevice::new();
+    device.proximity_crosstalk = [20, 30];
+    device.data_available = false;
+    let mut sensor = Apds9960::new(device);
+    sensor.enable().unwrap();
+    sensor.enable_light().unwrap();
+    sensor.enable_proximity_up_mask().unwrap();
+    sensor.set_proximity_offsets(3, -4).unwrap();
+    match sensor.calibrate_proximity_offsets() {
+        Err(Error::Timeout) => (),
+        _ => panic!("Timeout error not returned."),
+    }
+    sensor.enable_proximity_left_mask().unwrap();
+    let registers = sensor.destroy().registers;
+    assert_eq!(
+        BitFlags::PMASK_U | BitFlags::PMASK_L,
+        registers[usize::from(Register::CONFIG3)]
+    );
+    assert_eq!(
+        BitFlags::PON | BitFlags::AEN,
+        registers[usize::from(Register::ENABLE)]
+    );
+    assert_eq!(3, registers[usize::from(Register::POFFSET_UR)]);
+    assert_eq!(0x80 | 4, registers[usize::from(Register::POFFSET_DL)]);
+}
@@ -0,0 +1,294 @@
+//! Register map conformance tests.
+//!
+//! Register addresses and bit positions are written out literally here as
+//! documented in the APDS-9960 datasheet instead of using the constants in
+//! `common` so that a mistake in the driver or in those constants is caught.
+use apds9960::{
+    GestureDataThreshold, GestureDimensions, GestureExitPersistence, GestureGain,
+    GesturePulseLength, GestureWaitTime, LedBoost, LedDrive, LightGain, ProximityGain,
+    ProximityPulseLength,
+};
+use embedded_hal_mock::eh1::i2c::Transaction as I2cTrans;
+mod common;
+use common::{destroy, new, DEV_ADDR};
+
+macro_rules! write_reg_test {
+    ($name:ident, [$($byte:expr),*], $sensor:ident => $call:expr) => {
+        #[test]
+        fn $name() {
+            let trans = [I2cTrans::write(DEV_ADDR, vec![$($byte),*])];
+            let mut $sensor = new(&trans);
+            $call.unwrap();
+            destroy($sensor);
+        }
+    };
+}
+
+macro_rules! read_reg_test {
+    ($name:ident, $expected:expr, $([$reg:expr, [$($value:expr),*]]),*, $sensor:ident => $call:expr) => {
+        #[test]
+        fn $name() {
+            let trans = [
+                $(
+                    I2cTrans::write_read(DEV_ADDR, vec![$reg], vec![$($value),*]),
+                )*
+            ];
+            let mut $sensor = new(&trans);
+            assert_eq!($expected, $call.unwrap());
+            destroy($sensor);
+        }
+    };
+}
+
+// ENABLE (0x80)
+write_reg_test!(enable_pon, [0x80, 0b0000_0001], s => s.enable());
+write_reg_test!(enable_aen, [0x80, 0b0000_0010], s => s.enable_light());
+write_reg_test!(enable_pen, [0x80, 0b0000_0100], s => s.enable_proximity());
+write_reg_test!(enable_wen, [0x80, 0b0000_1000], s => s.enable_wait());
+write_reg_test!(enable_aien, [0x80, 0b0001_0000], s => s.enable_light_interrupts());
+write_reg_test!(enable_pien, [0x80, 0b0010_0000], s => s.enable_proximity_interrupts());
+write_reg_test!(enable_gen, [0x80, 0b0100_0000], s => s.enable_gesture());
+
+// ATIME (0x81), WTIME (0x83)
+write_reg_test!(atime, [0x81, 0xAB], s => s.set_light_integration_time(0xAB));
+write_reg_test!(wtime, [0x83, 0xAB], s => s.set_wait_time(0xAB));
+
+// AILTL/AILTH (0x84/0x85), AIHTL/AIHTH (0x86/0x87)
+write_reg_test!(ailt, [0x84, 0xCD, 0xAB], s => s.set_light_low_threshold(0xABCD));
+write_reg_test!(aiht, [0x86, 0xCD, 0xAB], s => s.set_light_high_threshold(0xABCD));
+
+// PILT (0x89), PIHT (0x8B)
+write_reg_test!(pilt, [0x89, 0xAB], s => s.set_proximity_low_threshold(0xAB));
+write_reg_test!(piht, [0x8B, 0xAB], s => s.set_proximity_high_threshold(0xAB));
+
+// PERS (0x8C): PPERS <7:4>, APERS <3:0>
+write_reg_test!(pers_ppers, [0x8C, 0b1111_0000], s => s.set_proximity_interrupt_persistence(15));
+write_reg_test!(pers_apers, [0x8C, 0b0000_1111], s => s.set_light_interrupt_persistence(60));
+
+// CONFIG1 (0x8D): reserved <6> written as 1, WLONG <1>
+write_reg_test!(config1_wlong, [0x8D, 0b0100_0010], s => s.enable_wait_long());
+
+// PPULSE (0x8E): PPLEN <7:6>, PPULSE <5:0>
+write_reg_test!(
+    ppulse_pplen,
+    [0x8E, 0b1100_0000],
+    s => s.set_proximity_pulse_length(ProximityPulseLength::Us32)
+);
+write_reg_test!(ppulse_ppulse, [0x8E, 0b0111_1111], s => s.set_proximity_pulse_count(64));
+
+// CONTROL (0x8F): LDRIVE <7:6>, PGAIN <3:2>, AGAIN <1:0>
+write_reg_test!(control_ldrive, [0x8F, 0b1100_0000], s => s.set_led_drive(LedDrive::Ma12_5));
+write_reg_test!(
+    control_pgain,
+    [0x8F, 0b0000_1100],
+    s => s.set_proximity_gain(ProximityGain::X8)
+);
+write_reg_test!(control_again, [0x8F, 0b0000_0011], s => s.set_light_gain(LightGain::X64));
+
+// CONFIG2 (0x90): PSIEN <7>, CPSIEN <6>, LED_BOOST <5:4>, reserved <0> written as 1
+write_reg_test!(
+    config2_psien,
+    [0x90, 0b1000_0001],
+    s => s.enable_proximity_saturation_interrupts()
+);
+write_reg_test!(
+    config2_cpsien,
+    [0x90, 0b0100_0001],
+    s => s.enable_light_saturation_interrupts()
+);
+write_reg_test!(
+    config2_led_boost,
+    [0x90, 0b0011_0001],
+    s => s.set_led_boost(LedBoost::Percent300)
+);
+
+// Power-on defaults of all writable registers
+#[test]
+fn reset_to_defaults() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![0x80, 0x00]),
+        I2cTrans::write(DEV_ADDR, vec![0x81, 0xFF]),
+        I2cTrans::write(DEV_ADDR, vec![0x83, 0xFF, 0x00, 0x00, 0x00, 0x00]),
+        I2cTrans::write(DEV_ADDR, vec![0x89, 0x00]),
+        I2cTrans::write(DEV_ADDR, vec![0x8B, 0x00, 0x00, 0x40, 0x40, 0x00, 0x01]),
+        I2cTrans::write(
+            DEV_ADDR,
+            vec![0x9D, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        ),
+        I2cTrans::write(DEV_ADDR, vec![0xA4, 0x00, 0x00, 0x40, 0x00]),
+        I2cTrans::write(DEV_ADDR, vec![0xA9, 0x00, 0x00, 0b0000_0100]),
+        I2cTrans::write(DEV_ADDR, vec![0xE7]),
+    ];
+    let mut sensor = new(&trans);
+    sensor.reset_to_defaults().unwrap();
+    destroy(sensor);
+}
+
+// ID (0x92)
+read_reg_test!(id, 0xAB, [0x92, [0xAB]], s => s.read_device_id());
+
+// STATUS (0x93): CPSAT <7>, PGSAT <6>, PINT <5>, AINT <4>, GINT <2>, PVALID <1>, AVALID <0>
+#[test]
+fn status() {
+    let trans = [
+        I2cTrans::write_read(DEV_ADDR, vec![0x93], vec![0b1000_0000]),
+        I2cTrans::write_read(DEV_ADDR, vec![0x93], vec![0b0100_0000]),
+        I2cTrans::write_read(DEV_ADDR, vec![0x93], vec![0b0010_0000]),
+        I2cTrans::write_read(DEV_ADDR, vec![0x93], vec![0b0001_0000]),
+        I2cTrans::write_read(DEV_ADDR, vec![0x93], vec![0b0000_0100]),
+    ];
+    let mut sensor = new(&trans);
+    assert!(sensor.read_status().unwrap().clear_photodiode_saturation);
+    assert!(sensor.read_status().unwrap().proximity_gesture_saturation);
+    assert!(sensor.read_status().unwrap().proximity_interrupt);
+    assert!(sensor.read_status().unwrap().light_interrupt);
+    assert!(sensor.read_status().unwrap().gesture_interrupt);
+    destroy(sensor);
+}
+read_reg_test!(status_avalid, true, [0x93, [0b0000_0001]], s => s.is_light_data_valid());
+read_reg_test!(status_pvalid, true, [0x93, [0b0000_0010]], s => s.is_proximity_data_valid());
+
+// CDATAL (0x94), RDATAL (0x96), GDATAL (0x98), BDATAL (0x9A), PDATA (0x9C)
+read_reg_test!(cdata, 0xABCD, [0x93, [1]], [0x94, [0xCD, 0xAB]], s => s.read_light_clear());
+read_reg_test!(rdata, 0xABCD, [0x93, [1]], [0x96, [0xCD, 0xAB]], s => s.read_light_red());
+read_reg_test!(gdata, 0xABCD, [0x93, [1]], [0x98, [0xCD, 0xAB]], s => s.read_light_green());
+read_reg_test!(bdata, 0xABCD, [0x93, [1]], [0x9A, [0xCD, 0xAB]], s => s.read_light_blue());
+read_reg_test!(pdata, 0xAB, [0x93, [2]], [0x9C, [0xAB]], s => s.read_proximity());
+
+// POFFSET_UR (0x9D), POFFSET_DL (0x9E): sign <7>, magnitude <6:0>
+write_reg_test!(poffset_ur, [0x9D, 0x81], s => s.set_proximity_up_right_offset(-1));
+write_reg_test!(poffset_dl, [0x9E, 0x7F], s => s.set_proximity_down_left_offset(127));
+write_reg_test!(poffsets, [0x9D, 0xFF, 0x7F], s => s.set_proximity_offsets(-127, 127));
+
+// CONFIG3 (0x9F): PCMP <5>, SAI <4>, PMASK_U <3>, PMASK_D <2>, PMASK_L <1>, PMASK_R <0>
+write_reg_test!(
+    config3_pcmp,
+    [0x9F, 0b0010_0000],
+    s => s.enable_proximity_gain_compensation()
+);
+write_reg_test!(config3_sai, [0x9F, 0b0001_0000], s => s.enable_sleep_after_interrupt());
+write_reg_test!(config3_pmask_u, [0x9F, 0b0000_1000], s => s.enable_proximity_up_mask());
+write_reg_test!(config3_pmask_d, [0x9F, 0b0000_0100], s => s.enable_proximity_down_mask());
+write_reg_test!(config3_pmask_l, [0x9F, 0b0000_0010], s => s.enable_proximity_left_mask());
+write_reg_test!(config3_pmask_r, [0x9F, 0b0000_0001], s => s.enable_proximity_right_mask());
+
+// GPENTH (0xA0), GEXTH (0xA1)
+write_reg_test!(gpenth, [0xA0, 0xAB], s => s.set_gesture_proximity_entry_threshold(0xAB));
+write_reg_test!(gexth, [0xA1, 0xAB], s => s.set_gesture_proximity_exit_threshold(0xAB));
+
+// GCONFIG1 (0xA2): GFIFOTH <7:6>, GEXMSK <5:2> (U, D, L, R), GEXPERS <1:0>
+write_reg_test!(
+    gconfig1_gfifoth,
+    [0xA2, 0b1100_0000],
+    s => s.set_gesture_data_level_threshold(GestureDataThreshold::Th16)
+);
+write_reg_test!(gconfig1_gexmsk_u, [0xA2, 0b0010_0000], s => s.enable_gesture_exit_up_mask());
+write_reg_test!(gconfig1_gexmsk_d, [0xA2, 0b0001_0000], s => s.enable_gesture_exit_down_mask());
+write_reg_test!(gconfig1_gexmsk_l, [0xA2, 0b0000_1000], s => s.enable_gesture_exit_left_mask());
+write_reg_test!(
+    gconfig1_gexmsk_r,
+    [0xA2, 0b0000_0100],
+    s => s.enable_gesture_exit_right_mask()
+);
+write_reg_test!(
+    gconfig1_gexpers,
+    [0xA2, 0b0000_0011],
+    s => s.set_gesture_exit_persistence(GestureExitPersistence::Seventh)
+);
+
+// GCONFIG2 (0xA3): GGAIN <6:5>, GLDRIVE <4:3>, GWTIME <2:0>
+write_reg_test!(gconfig2_ggain, [0xA3, 0b0110_0000], s => s.set_gesture_gain(GestureGain::X8));
+write_reg_test!(
+    gconfig2_gldrive,
+    [0xA3, 0b0001_1000],
+    s => s.set_gesture_led_drive(LedDrive::Ma12_5)
+);
+write_reg_test!(
+    gconfig2_gwtime,
+    [0xA3, 0b0000_0111],
+    s => s.set_gesture_wait_time(GestureWaitTime::Ms39_2)
+);
+
+// GOFFSET_U (0xA4), GOFFSET_D (0xA5), GOFFSET_L (0xA7), GOFFSET_R (0xA9):
+// sign <7>, magnitude <6:0>
+write_reg_test!(goffset_u, [0xA4, 0x81], s => s.set_gesture_up_offset(-1));
+write_reg_test!(goffset_d, [0xA5, 0xFF], s => s.set_gesture_down_offset(-127));
+write_reg_test!(goffset_l, [0xA7, 0x7F], s => s.set_gesture_left_offset(127));
+write_reg_test!(goffset_r, [0xA9, 0x81], s => s.set_gesture_right_offset(-1));
+
+#[test]
+fn goffsets() {
+    let trans = [
+        I2cTrans::write(DEV_ADDR, vec![0xA4, 0x01, 0x02]),
+        I2cTrans::write(DEV_ADDR, vec![0xA7, 0x03]),
+        I2cTrans::write(DEV_ADDR, vec![0xA9, 0x04]),
+    ];
+    let mut sensor = new(&trans);
+    sensor.set_gesture_offsets(1, 2, 3, 4).unwrap();
+    destroy(sensor);
+}
+
+// GPULSE (0xA6): GPLEN <7:6>, GPULSE <5:0>
+write_reg_test!(
+    gpulse_gplen,
+    [0xA6, 0b1100_0000],
+    s => s.set_gesture_pulse_length(GesturePulseLength::Us32)
+);
+write_reg_test!(gpulse_gpulse, [0xA6, 0b0111_1111], s => s.set_gesture_pulse_count(64));
+
+// GCONFIG3 (0xAA): GDIMS <1:0>
+write_reg_test!(
+    gconfig3_gdims_ud,
+    [0xAA, 0b0000_0001],
+    s => s.set_gesture_dimensions(GestureDimensions::UpDown)
+);
+write_reg_test!(
+    gconfig3_gdims_lr,
+    [0xAA, 0b0000_0010],
+    s => s.set_gesture_dimensions(GestureDimensions::LeftRight)
+);
+
+// GCONFIG4 (0xAB): GFIFO_CLR <2>, GIEN <1>, GMODE <0>
+write_reg_test!(gconfig4_gmode, [0xAB, 0b0000_0001], s => s.enable_gesture_mode());
+write_reg_test!(gconfig4_gien, [0xAB, 0b0000_0010], s => s.enable_gesture_interrupts());
+
+#[test]
+fn gconfig4_gfifo_clr() {
+    let trans = [
+        I2cTrans::write_read(DEV_ADDR, vec![0xAB], vec![0]),
+        I2cTrans::write(DEV_ADDR, vec![0xAB, 0b0000_0100]),
+    ];
+    let mut sensor = new(&trans);
+    sensor.clear_gesture_fifo().unwrap();
+    destroy(sensor);
+}
+
+read_reg_test!(gconfig4_gmode_read, true, [0xAB, [0b0000_0001]], s => s.is_gesture_mode_enabled());
+
+// GFLVL (0xAE)
+read_reg_test!(gflvl, 0xAB, [0xAE, [0xAB]], s => s.read_gesture_data_level());
+
+// GSTATUS (0xAF): GFOV <1>, GVALID <0>
+read_reg_test!(gstatus_gvalid, true, [0xAF, [0b0000_0001]], s => s.is_gesture_data_valid());
+read_reg_test!(gstatus_gfov, true, [0xAF, [0b0000_0010]], s => s.has_gesture_data_overflown());
+
+// IFORCE (0xE4), PICLEAR (0xE5), CICLEAR (0xE6), AICLEAR (0xE7)
+write_reg_test!(iforce, [0xE4], s => s.force_interrupt());
+write_reg_test!(piclear, [0xE5], s => s.clear_proximity_interrupt());
+write_reg_test!(ciclear, [0xE6], s => s.clear_light_interrupt());
+write_reg_test!(aiclear, [0xE7], s => s.clear_interrupts());
+
+// GFIFO_U (0xFC): followed by GFIFO_D, GFIFO_L and GFIFO_R
+#[test]
+fn gfifo() {
+    let trans = [
+        I2cTrans::write_read(DEV_ADDR, vec![0xAF], vec![1]),
+        I2cTrans::write_read(DEV_ADDR, vec![0xAE], vec![1]),
+        I2cTrans::write_read(DEV_ADDR, vec![0xFC], vec![1, 2, 3, 4]),
+    ];
+    let mut sensor = new(&trans);
+    let mut data = [0; 4];
+    sensor.read_gesture_data(&mut data).unwrap();
+    assert_eq!([1, 2, 3, 4], data);
+    destroy(sensor);
+}